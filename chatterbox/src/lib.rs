@@ -1,22 +1,59 @@
 use chrono::Datelike;
 use chrono::Month;
 use chrono::NaiveDate;
+use chrono::NaiveDateTime;
 use chrono::NaiveTime;
 use chrono::Utc;
+use chrono::Weekday;
+use event_manager::append_audit;
 use event_manager::create_schedule;
-use event_manager::get_config;
+use event_manager::delete_job;
+use event_manager::ensure_id;
+use event_manager::export_bundle;
+use event_manager::export_ics;
+use event_manager::format_times;
+use event_manager::get_chat_job;
+use event_manager::get_chat_jobs;
+use event_manager::get_last_day_of_month;
+use event_manager::get_owner;
+use event_manager::get_times_display;
+use event_manager::get_user_job;
+use event_manager::get_user_jobs;
+use event_manager::import_ics;
+use event_manager::is_job_id_taken;
+use event_manager::load_config;
+use event_manager::new_job_id;
+use event_manager::parse_bundle;
+use event_manager::parse_ics_dates;
+use event_manager::read_audit;
+use event_manager::update_config;
+use event_manager::validate_template;
+use event_manager::CatchUp;
 use event_manager::Config;
+use event_manager::Imported;
+use event_manager::IntervalUnit;
 pub use event_manager::Message;
+use event_manager::MissingDay;
 use event_manager::Schedule as emSchedule;
+pub use event_manager::Selection;
+use event_manager::Target;
+use event_manager::DEFAULT_GRACE_MINUTES;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::create_dir_all;
-use std::fs::read_link;
 use std::fs::read_to_string;
 use std::fs::remove_file;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+mod natural;
+
+use natural::parse_schedule;
+use natural::parse_times_text;
+use natural::parse_weekday;
+use natural::ParsedSchedule;
+
 pub enum FlowStatus {
     Done,
     DoneWithMessage(String),
@@ -27,41 +64,113 @@ pub enum FlowStatus {
     },
     Cancelled,
     Info(String),
-    Media(event_manager::Message),
+    Media(Vec<event_manager::Message>),
+    // media to show before asking the next step
+    Preview(Vec<event_manager::Message>, Coorespondance),
+    // a file to send, e.g. an export
+    File {
+        name: String,
+        content: String,
+    },
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Schedule {
     Daily,
     Weekly,
     Biweekly,
     Monthly,
     Yearly,
+    // every n days, weeks or months from the first date
+    Interval(u32, IntervalUnit),
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum DesiredValue {
     Message,
+    AnotherMessage,
+    Selection,
     Frequency,
+    Interval,
+    MonthlyRule,
     StartMonth,
     StartDay,
+    MissingDay,
     StartTime,
+    // checking a schedule that was typed out
+    ConfirmSchedule,
+    // optional start date, end date and number of deliveries
+    Limits,
+    StartDate,
+    EndDate,
+    MaxDeliveries,
+    SkipDates,
+    // how late missed deliveries may still be sent
+    GraceMinutes,
     Chat,
+    AnotherChat,
     HasToken,
     Token,
+    Confirm,
+    EditField,
+    ManagedChat,
+    // a backup or .ics file, then for calendars the chat their events post to
+    Import,
+    ImportChat,
     None,
 }
 
+/// Which day of the month a monthly job is sent on.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum MonthlyRule {
+    // the day of the first date picked
+    Date,
+    LastDay,
+    LastBusinessDay,
+    // 1 to 4 or -1 for the last, and the weekday counted from monday
+    NthWeekday(i32, u32),
+}
+
+/// The limit picked at the limits step, None once done.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+enum Limit {
+    StartDate,
+    EndDate,
+    MaxDeliveries,
+    // toggled right away
+    Weekends,
+    SkipDates,
+    // picked right away
+    CatchUp,
+    GraceMinutes,
+}
+
+/// The parts of a job that can be changed from the confirmation step.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Field {
+    Messages,
+    Schedule,
+    Chats,
+    Bot,
+}
+
 pub enum UserInput {
     Message(String),
     ChatLink(String),
+    Chat(Target),
     Frequency(Schedule),
+    Selection(Selection),
+    Field(Field),
     Time(String),
     Date(String),
+    Schedule(ParsedSchedule),
+    // file id of a calendar of days to skip, or of a file to import
+    Calendar(String),
     Media(Message),
     YesNo(bool),
     Delete(String),
     Command(Command),
+    Back,
 }
 
 pub enum Command {
@@ -70,6 +179,27 @@ pub enum Command {
     List,
     Delete(String),
     View(String),
+    Jobs(JobsAction),
+    Export(ExportFormat),
+    Import,
+}
+
+pub enum ExportFormat {
+    // backups, with everything needed to restore the jobs
+    Yaml,
+    Json,
+    Ics,
+}
+
+/// Managing the jobs of a chat, open to all of its administrators.
+pub enum JobsAction {
+    List,
+    View(String),
+    Delete(String),
+    Pause(String),
+    Resume(String),
+    Edit(String),
+    Audit,
 }
 
 impl Command {
@@ -81,37 +211,533 @@ impl Command {
             }
             Command::List => process_list(u_id),
             Command::Start => {
-                save_state(
-                    u_id,
-                    &ConfigInProgress {
-                        schedule: None,
-                        desired_value: DesiredValue::Message,
-                        first_execution_month: None,
-                        first_execution_day: None,
-                        first_execution_time: None,
-                        chat_id: None,
-                        message: None,
-                        has_token: None,
-                        token: None,
-                    },
-                );
+                save_state(u_id, &ConfigInProgress::new(DesiredValue::Message));
 
                 println!("returning send message request");
                 FlowStatus::Step(Coorespondance {
+                    back: false,
                     option_type: OptionType::Media,
                     message: "Please send the message you'd like sent.".to_owned(),
                 })
             }
             Command::Delete(to_delete) => {
-                event_manager::delete_scheduled(u_id, to_delete.parse::<i32>().unwrap());
-                FlowStatus::DoneWithMessage(format!("Successfully deleted message #{}", to_delete))
+                let (number, path, config) = match load_user_job(u_id, to_delete) {
+                    Ok(job) => job,
+                    Err(status) => return status,
+                };
+
+                // numbers shift as jobs come and go, so only an id deletes right away
+                if !config.has_id(to_delete) {
+                    return confirm_delete("/delete", &number, &path, &config);
+                }
+
+                audit_job(&path, &format!("user {} deleted", u_id));
+                delete_job(&path);
+                FlowStatus::DoneWithMessage(format!(
+                    "Successfully deleted message #{} [{}]",
+                    number,
+                    config.short_id()
+                ))
             }
-            Command::View(to_view) => FlowStatus::Media(
-                get_config(u_id, to_view.parse::<i32>().unwrap())
-                    .unwrap()
-                    .message,
+            Command::View(to_view) => match load_user_job(u_id, to_view) {
+                Ok((number, _, config)) => view_job(&number, config),
+                Err(status) => status,
+            },
+            Command::Export(format) => export_jobs(u_id, format),
+            Command::Import => {
+                let state = ConfigInProgress::new(DesiredValue::Import);
+                save_state(u_id, &state);
+                state.get_flow_status()
+            }
+            // needs a chat directory, handled by process_jobs
+            Command::Jobs(_) => FlowStatus::Error {
+                message: "Invalid command.".to_owned(),
+                desired_value: DesiredValue::None,
+            },
+        }
+    }
+}
+
+/// The user's jobs as a file in `format`.
+fn export_jobs(u_id: &String, format: &ExportFormat) -> FlowStatus {
+    let jobs = get_user_jobs(u_id)
+        .iter()
+        .filter_map(|(_, path)| {
+            Some((
+                load_config(path).ok()?,
+                emSchedule::from_file_location(path)?,
+            ))
+        })
+        .collect::<Vec<(Config, emSchedule)>>();
+
+    if jobs.is_empty() {
+        return FlowStatus::Error {
+            message: "You have no jobs to export.".to_owned(),
+            desired_value: DesiredValue::None,
+        };
+    }
+
+    let (name, content) = match format {
+        ExportFormat::Yaml => ("schedules.yaml", export_bundle(jobs, false)),
+        ExportFormat::Json => ("schedules.json", export_bundle(jobs, true)),
+        ExportFormat::Ics => ("schedules.ics", Ok(export_ics(&jobs, &Utc::now()))),
+    };
+
+    match content {
+        Ok(content) => FlowStatus::File {
+            name: name.to_owned(),
+            content,
+        },
+        Err(message) => FlowStatus::Error {
+            message,
+            desired_value: DesiredValue::None,
+        },
+    }
+}
+
+fn is_calendar(text: &str) -> bool {
+    text.contains("BEGIN:VCALENDAR")
+}
+
+// keeps a backup or calendar sent at the import step until it's processed
+fn load_import(state: &mut ConfigInProgress, text: &String) -> Result<(), String> {
+    if !is_calendar(text) {
+        parse_bundle(text)?;
+        state.import = Some(text.to_owned());
+        return Ok(());
+    }
+
+    let imported = import_ics(text, Utc::now().date_naive())?;
+
+    if imported.jobs.is_empty() {
+        return Err(format!(
+            "Nothing in that calendar could be imported:\n{}",
+            imported.report.join("\n")
+        ));
+    }
+
+    state.import = Some(text.to_owned());
+    Ok(())
+}
+
+// a backup is restored right away, a calendar still needs its chat
+fn process_import(
+    u_id: &String,
+    state: &mut ConfigInProgress,
+    chats: &dyn ChatDirectory,
+) -> FlowStatus {
+    let text = state.import.to_owned().unwrap_or_default();
+
+    if is_calendar(&text) {
+        state.move_to_next_step();
+        save_state(u_id, state);
+        return state.get_flow_status();
+    }
+
+    delete_state(u_id);
+    restore_bundle(u_id, &text, chats)
+}
+
+/// Recreates the jobs of a backup for the user. Each job is checked and its
+/// chats verified, jobs that already exist are left alone. A job whose id
+/// someone else's job has is restored under a new one.
+fn restore_bundle(u_id: &String, text: &str, chats: &dyn ChatDirectory) -> FlowStatus {
+    let bundle = match parse_bundle(text) {
+        Ok(bundle) => bundle,
+        Err(message) => {
+            return FlowStatus::Error {
+                message,
+                desired_value: DesiredValue::None,
+            }
+        }
+    };
+
+    // only the user's own jobs, others' aren't to be given away
+    let mut existing = get_user_jobs(u_id)
+        .iter()
+        .filter_map(|(_, path)| load_config(path).ok()?.id)
+        .collect::<HashSet<String>>();
+
+    let total = bundle.jobs.len();
+    let mut restored = 0;
+    let mut report = Vec::new();
+
+    for (i, mut entry) in bundle.jobs.into_iter().enumerate() {
+        let id = entry.job.id.get_or_insert_with(new_job_id).to_owned();
+        let label = format!("#{} [{}]", i + 1, entry.job.short_id());
+
+        let checked = entry
+            .validate()
+            .and_then(|()| match existing.contains(&id) {
+                true => Err("a job with its id already exists".to_owned()),
+                false => Ok(()),
+            })
+            .and_then(|()| {
+                entry
+                    .job
+                    .targets
+                    .iter()
+                    .map(|target| chats.verify_chat(target.to_owned(), u_id))
+                    .collect::<Result<Vec<Target>, String>>()
+            });
+
+        let targets = match checked {
+            Ok(targets) => targets,
+            Err(reason) => {
+                report.push(format!("{}: not restored, {}", label, reason));
+                continue;
+            }
+        };
+
+        entry.job.targets = targets.to_owned();
+        entry.job.owner = Some(u_id.to_owned());
+        // ids are unique, and whose job has it isn't the user's to know
+        if is_job_id_taken(&id) {
+            entry.job.id = Some(new_job_id());
+        }
+
+        if create_schedule(u_id, entry.job, entry.schedule) {
+            for target in targets.iter() {
+                append_audit(&target.chat_id, &format!("user {} restored a job", u_id));
+            }
+            existing.insert(id);
+            restored += 1;
+            report.push(format!("{}: restored", label));
+        } else {
+            report.push(format!("{}: could not be saved", label));
+        }
+    }
+
+    FlowStatus::DoneWithMessage(format!(
+        "Restored {} of {} job(s).\n{}",
+        restored,
+        total,
+        report.join("\n")
+    ))
+}
+
+fn process_import_chat(u_id: &String, state: &ConfigInProgress, message: UserInput) -> FlowStatus {
+    let target = match message {
+        UserInput::Chat(target) => target,
+        _ => panic!("Unsupported Input Type"),
+    };
+    delete_state(u_id);
+
+    let text = state.import.to_owned().unwrap_or_default();
+    let Imported { jobs, mut report } = match import_ics(&text, Utc::now().date_naive()) {
+        Ok(imported) => imported,
+        Err(message) => {
+            return FlowStatus::Error {
+                message,
+                desired_value: DesiredValue::None,
+            }
+        }
+    };
+
+    let mut count = 0;
+    for (mut config, schedule) in jobs {
+        config.targets = vec![target.to_owned()];
+        config.owner = Some(u_id.to_owned());
+        let description = describe_schedule(&schedule);
+
+        if create_schedule(u_id, config, schedule) {
+            append_audit(&target.chat_id, &format!("user {} imported a job", u_id));
+            count += 1;
+        } else {
+            report.push(format!("{} - could not be saved", description));
+        }
+    }
+
+    let mut message = format!("Imported {} job(s) posting to {}.", count, target);
+    if !report.is_empty() {
+        message.push_str(&format!("\n\nNot imported:\n{}", report.join("\n")));
+    }
+
+    FlowStatus::DoneWithMessage(message)
+}
+
+fn load_user_job(u_id: &String, reference: &str) -> Result<(String, String, Config), FlowStatus> {
+    let to_error = |message| FlowStatus::Error {
+        message,
+        desired_value: DesiredValue::None,
+    };
+
+    let (number, path) = get_user_job(u_id, reference).map_err(to_error)?;
+    let config = ensure_id(&path).map_err(to_error)?;
+
+    Ok((number, path, config))
+}
+
+fn confirm_delete(command: &str, number: &str, path: &str, config: &Config) -> FlowStatus {
+    let description = describe_job(&format!("#{}", number), path, config);
+
+    FlowStatus::Info(format!(
+        "This will delete\n{}\nSend {} {} to confirm.",
+        description.trim_end(),
+        command,
+        config.short_id()
+    ))
+}
+
+fn view_job(number: &String, config: Config) -> FlowStatus {
+    let mut messages = Vec::new();
+
+    if config.messages.len() > 1 {
+        messages.push(Message::Message(format!(
+            "Job #{} [{}] has {} messages, sent {}:",
+            number,
+            config.short_id(),
+            config.messages.len(),
+            get_selection_display(config.selection)
+        )));
+    }
+
+    messages.extend(preview_messages(&config));
+
+    FlowStatus::Media(messages)
+}
+
+/// The messages of a job, with templates previewed as if the job fired right now.
+fn preview_messages(config: &Config) -> Vec<Message> {
+    let mut messages = Vec::new();
+
+    let now = Utc::now();
+    for message in config.messages.iter() {
+        let chat_title = match config.targets.first() {
+            Some(target) => target.title.to_owned().unwrap_or(target.chat_id.to_owned()),
+            None => String::new(),
+        };
+        let preview = config.render(message.to_owned(), &now, &chat_title);
+
+        match (message, preview) {
+            (Message::Message(text), Message::Message(rendered)) if *text != rendered => {
+                messages.push(Message::Message(format!(
+                    "{}\n\nPreview:\n{}",
+                    text, rendered
+                )));
+            }
+            _ => messages.push(message.to_owned()),
+        }
+    }
+
+    messages
+}
+
+/// Writes `action` (e.g. "user 1 deleted") with a description of the job at
+/// `path` to the audit log of every chat the job posts to.
+fn audit_job(path: &String, action: &String) {
+    if let Ok(config) = load_config(path) {
+        let description = describe_job("job", path, &config);
+
+        for target in config.targets.iter() {
+            append_audit(
+                &target.chat_id,
+                &format!("{} {}", action, description.trim_end()),
+            );
+        }
+    }
+}
+
+fn process_jobs(u_id: &String, action: JobsAction, chats: &dyn ChatDirectory) -> FlowStatus {
+    match action {
+        JobsAction::List => {
+            save_state(u_id, &ConfigInProgress::new(DesiredValue::ManagedChat));
+
+            FlowStatus::Step(Coorespondance {
+                back: false,
+                option_type: OptionType::Chat,
+                message: "Which chat's jobs would you like to manage? Pick it with the button below, forward me a post from it, or send its @name or numeric id.".to_owned(),
+            })
+        }
+        _ => match get_managed_chat(u_id) {
+            Some(chat_id) => run_jobs_action(&chat_id, u_id, action, chats),
+            None => FlowStatus::Error {
+                message: "Please use /jobs first to pick a chat.".to_owned(),
+                desired_value: DesiredValue::None,
+            },
+        },
+    }
+}
+
+fn process_managed_chat(
+    u_id: &String,
+    message: UserInput,
+    chats: &dyn ChatDirectory,
+) -> FlowStatus {
+    match message {
+        UserInput::Chat(target) => {
+            delete_state(u_id);
+            set_managed_chat(u_id, &target.chat_id);
+            run_jobs_action(&target.chat_id, u_id, JobsAction::List, chats)
+        }
+        _ => panic!("Unsupported Input Type"),
+    }
+}
+
+/// Handles a message sent in a group or channel. Only the /jobs commands are
+/// answered there; anything else is left to the caller.
+pub fn accept_group_message(
+    chat_id: &String,
+    user_id: &String,
+    message: &Message,
+    chats: &dyn ChatDirectory,
+) -> Option<FlowStatus> {
+    let text = match message {
+        Message::Message(text) if text.to_lowercase().starts_with("/jobs") => text,
+        _ => return None,
+    };
+
+    match load_command(text) {
+        // the answers are given in private, not in front of the chat
+        Ok(UserInput::Command(Command::Jobs(JobsAction::Edit(_)))) => Some(FlowStatus::Error {
+            message: "Jobs are edited in a private chat with me, send /jobs there.".to_owned(),
+            desired_value: DesiredValue::None,
+        }),
+        Ok(UserInput::Command(Command::Jobs(action))) => {
+            Some(run_jobs_action(chat_id, user_id, action, chats))
+        }
+        Err(message) => Some(FlowStatus::Error {
+            message,
+            desired_value: DesiredValue::None,
+        }),
+        _ => None,
+    }
+}
+
+fn run_jobs_action(
+    chat_id: &String,
+    user_id: &String,
+    action: JobsAction,
+    chats: &dyn ChatDirectory,
+) -> FlowStatus {
+    if !chats.is_chat_admin(chat_id, user_id) {
+        return FlowStatus::Error {
+            message: "Only administrators of this chat can manage its jobs.".to_owned(),
+            desired_value: DesiredValue::None,
+        };
+    }
+
+    let reference = match &action {
+        JobsAction::List => {
+            let message = list_jobs(&get_chat_jobs(chat_id));
+
+            return FlowStatus::Info(if message.is_empty() {
+                "No jobs are scheduled for this chat.".to_owned()
+            } else {
+                message
+            });
+        }
+        JobsAction::Audit => {
+            let entries = read_audit(chat_id, 20);
+
+            return FlowStatus::Info(if entries.is_empty() {
+                "Nothing has been changed yet.".to_owned()
+            } else {
+                entries.join("\n")
+            });
+        }
+        JobsAction::View(reference)
+        | JobsAction::Delete(reference)
+        | JobsAction::Pause(reference)
+        | JobsAction::Resume(reference)
+        | JobsAction::Edit(reference) => reference.to_owned(),
+    };
+
+    let (number, path) = match get_chat_job(chat_id, &reference) {
+        Ok(job) => job,
+        Err(message) => {
+            return FlowStatus::Error {
+                message,
+                desired_value: DesiredValue::None,
+            }
+        }
+    };
+
+    let config = match ensure_id(&path) {
+        Ok(config) => config,
+        Err(message) => {
+            return FlowStatus::Error {
+                message,
+                desired_value: DesiredValue::None,
+            }
+        }
+    };
+
+    // a job posting to several chats may only be changed by admins of all of them
+    let foreign = config.targets.iter().find(|target| {
+        target.chat_id != *chat_id && !chats.is_chat_admin(&target.chat_id, user_id)
+    });
+
+    match (action, foreign) {
+        (JobsAction::View(_), _) => view_job(&number, config),
+        (_, Some(target)) => FlowStatus::Error {
+            message: format!(
+                "Job #{} also posts to {}, which you don't administer.",
+                number, target
             ),
+            desired_value: DesiredValue::None,
+        },
+        (JobsAction::Delete(_), None) if !config.has_id(&reference) => {
+            confirm_delete("/jobs delete", &number, &path, &config)
+        }
+        (JobsAction::Delete(_), None) => {
+            audit_job(&path, &format!("user {} deleted", user_id));
+            delete_job(&path);
+
+            FlowStatus::DoneWithMessage(format!(
+                "Successfully deleted job #{} [{}]",
+                number,
+                config.short_id()
+            ))
         }
+        (JobsAction::Pause(_), None) => {
+            let paused = update_config(&path, |config| {
+                config.disabled = Some(format!("paused by user {}", user_id));
+                Ok(())
+            });
+            if let Err(message) = paused {
+                return FlowStatus::Error {
+                    message,
+                    desired_value: DesiredValue::None,
+                };
+            }
+            audit_job(&path, &format!("user {} paused", user_id));
+
+            FlowStatus::DoneWithMessage(format!("Job #{} is paused.", number))
+        }
+        (JobsAction::Resume(_), None) => {
+            let resumed = update_config(&path, |config| {
+                config.disabled = None;
+                // nothing due while paused counts as missed
+                config.caught_up_at = Some(Utc::now());
+                Ok(())
+            });
+            if let Err(message) = resumed {
+                return FlowStatus::Error {
+                    message,
+                    desired_value: DesiredValue::None,
+                };
+            }
+            audit_job(&path, &format!("user {} resumed", user_id));
+
+            FlowStatus::DoneWithMessage(format!("Job #{} is running again.", number))
+        }
+        (JobsAction::Edit(_), None) => match emSchedule::from_file_location(&path) {
+            Some(schedule) => {
+                let state =
+                    ConfigInProgress::from_job(&path, &config, &schedule, Utc::now().date_naive());
+                save_state(user_id, &state);
+                state.get_flow_status()
+            }
+            None => FlowStatus::Error {
+                message: format!(
+                    "Job #{} can't be edited, its schedule can't be read.",
+                    number
+                ),
+                desired_value: DesiredValue::None,
+            },
+        },
+        (JobsAction::List, _) | (JobsAction::Audit, _) => unreachable!(),
     }
 }
 
@@ -123,9 +749,33 @@ impl UserInput {
             "biweekly" => Ok(UserInput::Frequency(Schedule::Biweekly)),
             "monthly" => Ok(UserInput::Frequency(Schedule::Monthly)),
             "yearly" => Ok(UserInput::Frequency(Schedule::Yearly)),
+            // the interval itself is asked for next
+            "other" => Ok(UserInput::Frequency(Schedule::Interval(
+                1,
+                IntervalUnit::Weeks,
+            ))),
             _ => Err(()),
         }
     }
+
+    pub fn parse_field(field: &str) -> Result<crate::UserInput, String> {
+        match field.to_lowercase().as_str() {
+            "messages" => Ok(UserInput::Field(Field::Messages)),
+            "schedule" => Ok(UserInput::Field(Field::Schedule)),
+            "chats" => Ok(UserInput::Field(Field::Chats)),
+            "bot" => Ok(UserInput::Field(Field::Bot)),
+            _ => get_error(&DesiredValue::EditField),
+        }
+    }
+
+    pub fn parse_selection(selection: &str) -> Result<crate::UserInput, String> {
+        match selection.to_lowercase().as_str() {
+            "in order" => Ok(UserInput::Selection(Selection::RoundRobin)),
+            "random" => Ok(UserInput::Selection(Selection::Random)),
+            "random, no repeats" => Ok(UserInput::Selection(Selection::Shuffle)),
+            _ => get_error(&DesiredValue::Selection),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -135,101 +785,973 @@ struct ConfigInProgress {
     pub first_execution_month: Option<String>,
     pub first_execution_day: Option<String>,
     pub first_execution_time: Option<String>,
-    pub chat_id: Option<String>,
-    pub message: Option<Message>,
+    // only set when a full date was picked from the calendar
+    #[serde(default)]
+    pub first_execution_year: Option<i32>,
+    #[serde(default)]
+    pub chats: Vec<Target>,
+    #[serde(default)]
+    pub another_chat: Option<bool>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub another_message: Option<bool>,
+    // back at the message step, the last message is replaced by the next one
+    #[serde(default)]
+    pub replacing_message: bool,
+    #[serde(default)]
+    pub selection: Option<Selection>,
     pub has_token: Option<bool>,
     pub token: Option<String>,
+    // the @username of the bot sending, for the summary
+    #[serde(default)]
+    pub bot_name: Option<String>,
+    // set while changing a single field from the confirmation step
+    #[serde(default)]
+    pub editing: Option<Field>,
+    #[serde(default)]
+    pub schedule_confirmed: Option<bool>,
+    // a date or time typed before the frequency, confirmed once it's picked
+    #[serde(default)]
+    pub typed_date: bool,
+    #[serde(default)]
+    pub missing_day: Option<MissingDay>,
+    #[serde(default)]
+    pub monthly_rule: Option<MonthlyRule>,
+    #[serde(default)]
+    pub limit: Option<Limit>,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub max_deliveries: Option<u32>,
+    #[serde(default)]
+    pub skip_weekends: bool,
+    #[serde(default)]
+    pub skip_dates: Vec<NaiveDate>,
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    #[serde(default)]
+    pub grace_minutes: Option<u32>,
+    // while picking several times, until Done
+    #[serde(default)]
+    pub picking_times: bool,
+    // the calendar being imported
+    #[serde(default)]
+    pub import: Option<String>,
+    // tags the buttons of this wizard so presses from older ones are ignored
+    #[serde(default)]
+    pub session: Option<String>,
+    // the schedule file of the job being edited, replaced once confirmed
+    #[serde(default)]
+    pub edited_job: Option<String>,
 }
 
 impl ConfigInProgress {
+    pub fn new(desired_value: DesiredValue) -> ConfigInProgress {
+        ConfigInProgress {
+            schedule: None,
+            desired_value,
+            first_execution_month: None,
+            first_execution_day: None,
+            first_execution_time: None,
+            first_execution_year: None,
+            chats: Vec::new(),
+            another_chat: None,
+            messages: Vec::new(),
+            another_message: None,
+            replacing_message: false,
+            selection: None,
+            has_token: None,
+            token: None,
+            bot_name: None,
+            editing: None,
+            schedule_confirmed: None,
+            typed_date: false,
+            missing_day: None,
+            monthly_rule: None,
+            limit: None,
+            start_date: None,
+            end_date: None,
+            max_deliveries: None,
+            skip_weekends: false,
+            catch_up: CatchUp::Once,
+            grace_minutes: None,
+            skip_dates: Vec::new(),
+            picking_times: false,
+            import: None,
+            session: Some(new_session_id()),
+            edited_job: None,
+        }
+    }
+
+    /// The answers that would make the job at `path`, to change some of them.
+    /// Dates are the next ones the job is sent on after `today`.
+    fn from_job(path: &str, config: &Config, schedule: &emSchedule, today: NaiveDate) -> Self {
+        let mut state = ConfigInProgress::new(DesiredValue::EditField);
+        state.edited_job = Some(path.to_owned());
+        state.messages = config.messages.to_owned();
+        state.selection = Some(config.selection);
+        state.chats = config.targets.to_owned();
+        state.has_token = Some(false);
+        state.missing_day = Some(config.missing_day);
+        state.start_date = config.start_date;
+        state.end_date = config.end_date;
+        state.max_deliveries = config.max_deliveries;
+        state.skip_weekends = config.skip_weekends;
+        state.skip_dates = config.skip_dates.to_owned();
+        state.catch_up = config.catch_up;
+        state.grace_minutes = config.grace_minutes;
+        state.first_execution_time = Some(schedule.get_time().to_owned());
+
+        let next = |wanted: &dyn Fn(&NaiveDate) -> bool| {
+            // february 29th can be a few years off
+            today.iter_days().take(366 * 8).find(|date| wanted(date))
+        };
+        let (frequency, rule, date) = match schedule {
+            emSchedule::Daily { .. } => (Schedule::Daily, None, None),
+            emSchedule::Weekly { weekday, .. } => (
+                Schedule::Weekly,
+                None,
+                next(&|date| date.weekday() == *weekday),
+            ),
+            emSchedule::Interval {
+                every: 2,
+                unit: IntervalUnit::Weeks,
+                anchor,
+                ..
+            } => (Schedule::Biweekly, None, Some(*anchor)),
+            emSchedule::Interval {
+                every,
+                unit,
+                anchor,
+                ..
+            } => (Schedule::Interval(*every, *unit), None, Some(*anchor)),
+            emSchedule::Monthly { day, .. } => (
+                Schedule::Monthly,
+                Some(MonthlyRule::Date),
+                next(&|date| date.day() as i32 == *day),
+            ),
+            emSchedule::Yearly { day, month, .. } => (
+                Schedule::Yearly,
+                None,
+                next(&|date| {
+                    date.day() as i32 == *day && date.month() == month.number_from_month()
+                }),
+            ),
+            emSchedule::MonthlyLastDay { .. } => {
+                (Schedule::Monthly, Some(MonthlyRule::LastDay), None)
+            }
+            emSchedule::MonthlyLastBusinessDay { .. } => {
+                (Schedule::Monthly, Some(MonthlyRule::LastBusinessDay), None)
+            }
+            emSchedule::MonthlyNthWeekday { nth, weekday, .. } => (
+                Schedule::Monthly,
+                Some(MonthlyRule::NthWeekday(
+                    *nth,
+                    weekday.num_days_from_monday(),
+                )),
+                None,
+            ),
+        };
+
+        state.schedule = Some(frequency);
+        state.monthly_rule = rule;
+        if let Some(date) = date {
+            state.set_first_execution_date(date);
+        }
+
+        state
+    }
+
+    fn set_first_execution_date(&mut self, date: NaiveDate) {
+        self.first_execution_year = Some(date.year());
+        self.first_execution_month = Some(string_from_month(
+            get_month_from_int(date.month() as i32).unwrap(),
+        ));
+        self.first_execution_day = Some(date.day().to_string());
+    }
+
     pub fn move_to_next_step(&mut self) {
-        self.desired_value = match self.desired_value {
-            DesiredValue::Message => DesiredValue::Frequency,
+        let next = match self.desired_value {
+            DesiredValue::Message => DesiredValue::AnotherMessage,
+            DesiredValue::AnotherMessage => {
+                if self.another_message.unwrap() {
+                    DesiredValue::Message
+                } else if self.messages.len() > 1 {
+                    DesiredValue::Selection
+                } else {
+                    DesiredValue::Frequency
+                }
+            }
+            DesiredValue::Selection => DesiredValue::Frequency,
             DesiredValue::Frequency => match self.schedule.as_ref().unwrap() {
+                Schedule::Interval(_, _) => DesiredValue::Interval,
+                _ if self.typed_date => DesiredValue::ConfirmSchedule,
                 Schedule::Daily => DesiredValue::StartTime,
+                Schedule::Monthly => DesiredValue::MonthlyRule,
                 _ => DesiredValue::StartMonth,
             },
-            DesiredValue::StartMonth => DesiredValue::StartDay,
-            DesiredValue::StartDay => DesiredValue::StartTime,
-            DesiredValue::StartTime => DesiredValue::Chat,
-            DesiredValue::Chat => DesiredValue::HasToken,
+            DesiredValue::Interval => match self.typed_date {
+                true => DesiredValue::ConfirmSchedule,
+                false => DesiredValue::StartMonth,
+            },
+            DesiredValue::MonthlyRule => match self.get_monthly_rule() {
+                Some(_) => DesiredValue::StartTime,
+                None => DesiredValue::StartMonth,
+            },
+            DesiredValue::StartMonth => match self.first_execution_year {
+                Some(_) if self.has_missing_days() => DesiredValue::MissingDay,
+                Some(_) => DesiredValue::StartTime,
+                None => DesiredValue::StartDay,
+            },
+            DesiredValue::StartDay => match self.has_missing_days() {
+                true => DesiredValue::MissingDay,
+                false => DesiredValue::StartTime,
+            },
+            DesiredValue::MissingDay => DesiredValue::StartTime,
+            DesiredValue::StartTime => match self.picking_times {
+                true => DesiredValue::StartTime,
+                false => DesiredValue::Limits,
+            },
+            // ask for whatever the typed schedule left out
+            DesiredValue::ConfirmSchedule => match self.schedule_confirmed {
+                Some(true) if self.has_missing_days() && self.missing_day.is_none() => {
+                    DesiredValue::MissingDay
+                }
+                Some(true) => match (&self.schedule, &self.first_execution_day) {
+                    (Some(Schedule::Daily), _) | (_, Some(_)) => match self.first_execution_time {
+                        Some(_) => DesiredValue::Limits,
+                        None => DesiredValue::StartTime,
+                    },
+                    _ => DesiredValue::StartMonth,
+                },
+                _ => DesiredValue::Frequency,
+            },
+            DesiredValue::Limits => match self.limit {
+                Some(Limit::StartDate) => DesiredValue::StartDate,
+                Some(Limit::EndDate) => DesiredValue::EndDate,
+                Some(Limit::MaxDeliveries) => DesiredValue::MaxDeliveries,
+                Some(Limit::Weekends) | Some(Limit::CatchUp) => DesiredValue::Limits,
+                Some(Limit::SkipDates) => DesiredValue::SkipDates,
+                Some(Limit::GraceMinutes) => DesiredValue::GraceMinutes,
+                None => DesiredValue::Chat,
+            },
+            DesiredValue::StartDate
+            | DesiredValue::EndDate
+            | DesiredValue::MaxDeliveries
+            | DesiredValue::SkipDates
+            | DesiredValue::GraceMinutes => DesiredValue::Limits,
+            DesiredValue::Chat => DesiredValue::AnotherChat,
+            DesiredValue::AnotherChat => {
+                if self.another_chat.unwrap() {
+                    DesiredValue::Chat
+                } else {
+                    DesiredValue::HasToken
+                }
+            }
             DesiredValue::HasToken => {
                 if self.has_token.unwrap() {
                     DesiredValue::Token
                 } else {
-                    DesiredValue::None
+                    DesiredValue::Confirm
                 }
             }
-            DesiredValue::Token => DesiredValue::None,
+            DesiredValue::Token => DesiredValue::Confirm,
+            DesiredValue::Confirm => DesiredValue::EditField,
+            DesiredValue::EditField => match self.editing {
+                Some(Field::Messages) => DesiredValue::Message,
+                Some(Field::Schedule) => DesiredValue::Frequency,
+                Some(Field::Chats) => DesiredValue::Chat,
+                Some(Field::Bot) | None => DesiredValue::HasToken,
+            },
+            DesiredValue::Import => DesiredValue::ImportChat,
+            DesiredValue::ManagedChat | DesiredValue::ImportChat => DesiredValue::None,
             DesiredValue::None => DesiredValue::None,
+        };
+
+        // when editing, go back to the summary once the field's steps are done
+        let edited = matches!(
+            (&self.editing, &next),
+            (Some(Field::Messages), DesiredValue::Frequency)
+                | (Some(Field::Schedule), DesiredValue::Chat)
+                | (Some(Field::Chats), DesiredValue::HasToken)
+                | (_, DesiredValue::Confirm)
+        );
+
+        if edited {
+            self.editing = None;
+            self.desired_value = DesiredValue::Confirm;
+        } else {
+            self.desired_value = next;
+        }
+    }
+
+    pub fn get_flow_status(&self) -> FlowStatus {
+        match self.desired_value {
+            DesiredValue::Message => FlowStatus::Step(self.get_message()),
+            DesiredValue::AnotherMessage => FlowStatus::Step(self.get_message()),
+            DesiredValue::Selection => FlowStatus::Step(self.get_message()),
+            DesiredValue::Frequency => FlowStatus::Step(self.get_message()),
+            DesiredValue::Interval => FlowStatus::Step(self.get_message()),
+            DesiredValue::MonthlyRule => FlowStatus::Step(self.get_message()),
+            DesiredValue::StartMonth => FlowStatus::Step(self.get_message()),
+            DesiredValue::StartDay => FlowStatus::Step(self.get_message()),
+            DesiredValue::MissingDay => FlowStatus::Step(self.get_message()),
+            DesiredValue::StartTime => FlowStatus::Step(self.get_message()),
+            DesiredValue::ConfirmSchedule => FlowStatus::Step(self.get_message()),
+            DesiredValue::Limits => FlowStatus::Step(self.get_message()),
+            DesiredValue::StartDate => FlowStatus::Step(self.get_message()),
+            DesiredValue::EndDate => FlowStatus::Step(self.get_message()),
+            DesiredValue::MaxDeliveries => FlowStatus::Step(self.get_message()),
+            DesiredValue::SkipDates => FlowStatus::Step(self.get_message()),
+            DesiredValue::GraceMinutes => FlowStatus::Step(self.get_message()),
+            DesiredValue::Chat => FlowStatus::Step(self.get_message()),
+            DesiredValue::AnotherChat => FlowStatus::Step(self.get_message()),
+            DesiredValue::HasToken => FlowStatus::Step(self.get_message()),
+            DesiredValue::Confirm => {
+                let mut preview = self.to_config();
+                preview.id = None;
+
+                FlowStatus::Preview(preview_messages(&preview), self.get_message())
+            }
+            DesiredValue::EditField => FlowStatus::Step(self.get_message()),
+            DesiredValue::Token => FlowStatus::Step(self.get_message()),
+            DesiredValue::Import | DesiredValue::ImportChat => FlowStatus::Step(self.get_message()),
+            DesiredValue::ManagedChat | DesiredValue::None => FlowStatus::Done,
+        }
+    }
+
+    /// The rule of a monthly job that isn't sent on a fixed date.
+    fn get_monthly_rule(&self) -> Option<MonthlyRule> {
+        match (&self.schedule, self.monthly_rule) {
+            (Some(Schedule::Monthly), Some(MonthlyRule::Date)) => None,
+            (Some(Schedule::Monthly), rule) => rule,
+            _ => None,
+        }
+    }
+
+    // whether the first date picked is missing from some months or years
+    fn has_missing_days(&self) -> bool {
+        if self.get_monthly_rule().is_some() {
+            return false;
+        }
+
+        let day = match &self.first_execution_day {
+            Some(day) => day.parse::<u32>().unwrap_or(0),
+            None => return false,
+        };
+
+        match self.schedule {
+            Some(Schedule::Monthly) | Some(Schedule::Interval(_, IntervalUnit::Months)) => day > 28,
+            Some(Schedule::Yearly) => {
+                get_month_number(&self.first_execution_month) == 2 && day == 29
+            }
+            _ => false,
+        }
+    }
+
+    // e.g. "from 2026-11-01, until 2026-12-31, at most 10 times"
+    fn get_limits_display(&self) -> Option<String> {
+        let mut limits = Vec::new();
+
+        if let Some(start) = self.start_date {
+            limits.push(format!("from {}", start));
+        }
+        if let Some(end) = self.end_date {
+            limits.push(format!("until {}", end));
+        }
+        if let Some(max) = self.max_deliveries {
+            limits.push(format!("at most {} times", max));
+        }
+        if let Some(skips) = self.get_skips_display() {
+            limits.push(format!("skipping {}", skips));
+        }
+        if let Some(catch_up) = self.to_config().get_catch_up_display() {
+            limits.push(format!("missed deliveries {}", catch_up));
+        }
+
+        match limits.is_empty() {
+            true => None,
+            false => Some(limits.join(", ")),
+        }
+    }
+
+    // same wording as /list
+    fn get_skips_display(&self) -> Option<String> {
+        self.to_config().get_skips_display()
+    }
+
+    /// The job as it would be saved, without its schedule.
+    fn to_config(&self) -> Config {
+        let mut config = Config::new(
+            self.chats.to_owned(),
+            self.messages.to_owned(),
+            self.selection.unwrap_or_default(),
+        );
+
+        if self.has_missing_days() {
+            config.missing_day = self.missing_day.unwrap_or_default();
+        }
+        config.start_date = self.start_date;
+        config.end_date = self.end_date;
+        config.max_deliveries = self.max_deliveries;
+        config.skip_weekends = self.skip_weekends;
+        config.skip_dates = self.skip_dates.to_owned();
+        config.catch_up = self.catch_up;
+        config.grace_minutes = self.grace_minutes;
+
+        config
+    }
+
+    /// The schedule as it would be saved, None until it's complete.
+    fn to_schedule(&self) -> Option<emSchedule> {
+        let time = self.first_execution_time.to_owned()?;
+
+        if let Some(rule) = self.get_monthly_rule() {
+            return Some(match rule {
+                MonthlyRule::LastDay => emSchedule::MonthlyLastDay { time },
+                MonthlyRule::LastBusinessDay => emSchedule::MonthlyLastBusinessDay { time },
+                MonthlyRule::NthWeekday(nth, weekday) => emSchedule::MonthlyNthWeekday {
+                    nth,
+                    weekday: get_weekday_from_int(weekday),
+                    time,
+                },
+                MonthlyRule::Date => unreachable!(),
+            });
+        }
+
+        let schedule = self.schedule.as_ref()?;
+        if matches!(schedule, Schedule::Daily) {
+            return Some(emSchedule::Daily { time });
+        }
+
+        // a typed schedule may not have a date yet
+        self.first_execution_month.as_ref()?;
+        self.first_execution_day.as_ref()?;
+        let date = get_first_execution_date(self);
+
+        Some(match schedule {
+            Schedule::Daily => unreachable!(),
+            Schedule::Weekly => emSchedule::Weekly {
+                weekday: date.weekday(),
+                time,
+            },
+            Schedule::Biweekly => emSchedule::Interval {
+                every: 2,
+                unit: IntervalUnit::Weeks,
+                anchor: date,
+                time,
+            },
+            Schedule::Interval(every, unit) => emSchedule::Interval {
+                every: *every,
+                unit: *unit,
+                anchor: date,
+                time,
+            },
+            Schedule::Monthly => emSchedule::Monthly {
+                day: date.day() as i32,
+                time,
+            },
+            Schedule::Yearly => emSchedule::Yearly {
+                month: get_month_from_int(date.month() as i32).unwrap(),
+                day: date.day() as i32,
+                time,
+            },
+        })
+    }
+
+    fn get_summary(&self) -> String {
+        let mut summary = String::from("Please check your job before I save it.\n\n");
+
+        summary.push_str(&match self.messages.len() {
+            1 => "Message: shown above\n".to_owned(),
+            count => format!(
+                "Messages: the {} above, sent {}\n",
+                count,
+                get_selection_display(self.selection.unwrap_or_default())
+            ),
+        });
+        summary.push_str(&format!("Schedule: {}\n", self.get_schedule_display()));
+        if let Some(limits) = self.get_limits_display() {
+            summary.push_str(&format!("Limits: {}\n", limits));
+        }
+
+        summary.push_str("Next times (UTC):\n");
+        for occurrence in get_next_occurrences(self, &Utc::now().naive_utc(), 5) {
+            summary.push_str(&format!("   {}\n", occurrence.format("%a %d %b %Y %H:%M")));
+        }
+
+        let chats = self
+            .chats
+            .iter()
+            .map(|target| target.to_string())
+            .collect::<Vec<String>>();
+        summary.push_str(&format!("Posting to: {}\n", chats.join(", ")));
+
+        let sender = match (&self.bot_name, self.has_token, &self.token) {
+            (Some(name), _, _) => name.to_owned(),
+            // the numeric part of the token is the bot's user id
+            (None, Some(true), Some(token)) => {
+                format!("bot {}", token.split(':').next().unwrap_or_default())
+            }
+            _ => "me".to_owned(),
+        };
+        summary.push_str(&format!("Sent by: {}", sender));
+
+        summary
+    }
+
+    // the date and time typed before a frequency was picked
+    fn get_typed_date_display(&self) -> String {
+        let date = self.first_execution_day.as_ref().map(|_| {
+            format!(
+                "first on {}",
+                get_first_execution_date(self).format("%a %-d %b %Y")
+            )
+        });
+        let time = self.first_execution_time.as_deref().map(get_times_display);
+
+        match (date, time) {
+            (Some(date), Some(time)) => format!("{} {}", date, time),
+            (Some(text), None) | (None, Some(text)) => text,
+            (None, None) => "no date yet".to_owned(),
+        }
+    }
+
+    fn get_schedule_display(&self) -> String {
+        // a typed schedule may not have a date or time yet
+        let time = match &self.first_execution_time {
+            Some(time) => format!(" {}", get_times_display(time)),
+            None => String::new(),
+        };
+
+        if let Some(rule) = self.get_monthly_rule() {
+            return format!(
+                "monthly on the {}{}",
+                get_monthly_rule_description(rule),
+                time
+            );
+        }
+
+        match (self.schedule.as_ref().unwrap(), &self.first_execution_day) {
+            (Schedule::Daily, _) => format!("daily{}", time),
+            (schedule, Some(_)) => {
+                let date = get_first_execution_date(self);
+
+                let missing = match (self.has_missing_days(), self.missing_day) {
+                    (true, Some(missing_day)) => format!(
+                        " ({} when the day is missing)",
+                        get_missing_day_display(missing_day).to_lowercase()
+                    ),
+                    _ => String::new(),
+                };
+
+                match schedule {
+                    Schedule::Weekly => format!("weekly on {}{}", date.format("%A"), time),
+                    Schedule::Biweekly => format!("every other {}{}", date.format("%A"), time),
+                    Schedule::Monthly => {
+                        format!("monthly on day {}{}{}", date.day(), time, missing)
+                    }
+                    Schedule::Interval(every, unit) => format!(
+                        "every {} {} from {}{}{}",
+                        every,
+                        unit,
+                        date.format("%a %-d %b %Y"),
+                        time,
+                        missing
+                    ),
+                    _ => format!("yearly on {}{}{}", date.format("%B %-d"), time, missing),
+                }
+            }
+            (Schedule::Weekly, None) => format!("weekly{}", time),
+            (Schedule::Biweekly, None) => format!("every other week{}", time),
+            (Schedule::Monthly, None) => format!("monthly{}", time),
+            (Schedule::Yearly, None) => format!("yearly{}", time),
+            (Schedule::Interval(every, unit), None) => format!("every {} {}{}", every, unit, time),
+        }
+    }
+
+    // times picked so far, none when they were typed as a range
+    fn get_picked_times(&self) -> Vec<String> {
+        match &self.first_execution_time {
+            Some(time) if !time.starts_with("every-") => {
+                time.split(',').map(|time| time.to_owned()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn get_message(&self) -> Coorespondance {
+        let step = self.get_step_message();
+
+        // after going back, show what was entered before
+        let message = match self.get_current_value() {
+            Some(current) => format!("{}\n\nCurrently: {}", step.message, current),
+            None => step.message,
+        };
+
+        Coorespondance {
+            option_type: step.option_type,
+            message,
+            back: self.get_previous_step().is_some(),
+        }
+    }
+
+    fn get_current_value(&self) -> Option<String> {
+        match self.desired_value {
+            DesiredValue::Selection => self.selection.map(get_selection_display),
+            DesiredValue::Frequency => self.schedule.as_ref().map(|schedule| match schedule {
+                Schedule::Daily => "Daily".to_owned(),
+                Schedule::Weekly => "Weekly".to_owned(),
+                Schedule::Biweekly => "Biweekly".to_owned(),
+                Schedule::Monthly => "Monthly".to_owned(),
+                Schedule::Yearly => "Yearly".to_owned(),
+                Schedule::Interval(_, _) => "Other".to_owned(),
+            }),
+            DesiredValue::Interval => match self.schedule {
+                Some(Schedule::Interval(every, unit)) => Some(format!("every {} {}", every, unit)),
+                _ => None,
+            },
+            DesiredValue::MonthlyRule => self.monthly_rule.map(get_monthly_rule_display),
+            DesiredValue::StartMonth => match self.first_execution_year {
+                Some(_) => Some(
+                    get_first_execution_date(self)
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                ),
+                None => self.first_execution_month.to_owned(),
+            },
+            DesiredValue::StartDay => self.first_execution_day.to_owned(),
+            DesiredValue::MissingDay => self.missing_day.map(get_missing_day_display),
+            DesiredValue::StartTime => self
+                .first_execution_time
+                .as_ref()
+                .map(|time| get_times_display(time)),
+            DesiredValue::Limits => self.get_limits_display(),
+            DesiredValue::StartDate => self.start_date.map(|date| date.to_string()),
+            DesiredValue::EndDate => self.end_date.map(|date| date.to_string()),
+            DesiredValue::MaxDeliveries => self.max_deliveries.map(|max| max.to_string()),
+            DesiredValue::GraceMinutes => self.grace_minutes.map(|grace| grace.to_string()),
+            DesiredValue::SkipDates => match self.skip_dates.len() {
+                0 => None,
+                count => Some(format!("{} dates", count)),
+            },
+            DesiredValue::HasToken => self.has_token.map(|answer| match answer {
+                true => "Yes".to_owned(),
+                false => "No".to_owned(),
+            }),
+            DesiredValue::Token => self.token.as_ref().map(|token| {
+                let visible = token.chars().count().saturating_sub(4);
+                format!("...{}", token.chars().skip(visible).collect::<String>())
+            }),
+            DesiredValue::Message if self.replacing_message => {
+                self.messages.last().map(|message| match message {
+                    Message::Message(text) => text.to_owned(),
+                    _ => "the file you sent".to_owned(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The step before the current one, following the same branches as
+    /// move_to_next_step. None at the first step.
+    fn get_previous_step(&self) -> Option<DesiredValue> {
+        let edit_start = match (&self.editing, &self.desired_value) {
+            (Some(Field::Messages), DesiredValue::Message) => self.messages.is_empty(),
+            (Some(Field::Schedule), DesiredValue::Frequency) => true,
+            (Some(Field::Chats), DesiredValue::Chat) => self.chats.is_empty(),
+            (Some(Field::Bot), DesiredValue::HasToken) => true,
+            _ => false,
+        };
+        if edit_start {
+            return Some(DesiredValue::EditField);
+        }
+
+        match self.desired_value {
+            DesiredValue::Message => match self.messages.is_empty() {
+                true => None,
+                false => Some(DesiredValue::AnotherMessage),
+            },
+            DesiredValue::AnotherMessage => Some(DesiredValue::Message),
+            DesiredValue::Selection => Some(DesiredValue::AnotherMessage),
+            DesiredValue::Frequency => match self.messages.len() > 1 {
+                true => Some(DesiredValue::Selection),
+                false => Some(DesiredValue::AnotherMessage),
+            },
+            DesiredValue::Interval => Some(DesiredValue::Frequency),
+            DesiredValue::MonthlyRule => Some(DesiredValue::Frequency),
+            DesiredValue::StartMonth => match self.schedule {
+                Some(Schedule::Monthly) => Some(DesiredValue::MonthlyRule),
+                Some(Schedule::Interval(_, _)) => Some(DesiredValue::Interval),
+                _ => Some(DesiredValue::Frequency),
+            },
+            DesiredValue::StartDay => Some(DesiredValue::StartMonth),
+            DesiredValue::MissingDay => match self.first_execution_year {
+                Some(_) => Some(DesiredValue::StartMonth),
+                None => Some(DesiredValue::StartDay),
+            },
+            DesiredValue::StartTime => match (&self.schedule, self.first_execution_year) {
+                (Some(Schedule::Daily), _) => Some(DesiredValue::Frequency),
+                _ if self.get_monthly_rule().is_some() => Some(DesiredValue::MonthlyRule),
+                _ if self.has_missing_days() => Some(DesiredValue::MissingDay),
+                (_, Some(_)) => Some(DesiredValue::StartMonth),
+                _ => Some(DesiredValue::StartDay),
+            },
+            DesiredValue::ConfirmSchedule => Some(DesiredValue::Frequency),
+            DesiredValue::Limits => Some(DesiredValue::StartTime),
+            DesiredValue::StartDate
+            | DesiredValue::EndDate
+            | DesiredValue::MaxDeliveries
+            | DesiredValue::SkipDates
+            | DesiredValue::GraceMinutes => Some(DesiredValue::Limits),
+            DesiredValue::Chat => match self.chats.is_empty() {
+                true => Some(DesiredValue::Limits),
+                false => Some(DesiredValue::AnotherChat),
+            },
+            DesiredValue::AnotherChat => Some(DesiredValue::Chat),
+            DesiredValue::HasToken => Some(DesiredValue::AnotherChat),
+            DesiredValue::Token => Some(DesiredValue::HasToken),
+            DesiredValue::Confirm => match self.has_token {
+                Some(true) => Some(DesiredValue::Token),
+                _ => Some(DesiredValue::HasToken),
+            },
+            DesiredValue::EditField => Some(DesiredValue::Confirm),
+            DesiredValue::ManagedChat
+            | DesiredValue::Import
+            | DesiredValue::ImportChat
+            | DesiredValue::None => None,
         }
     }
 
-    pub fn get_flow_status(&self) -> FlowStatus {
-        match self.desired_value {
-            DesiredValue::Message => FlowStatus::Step(self.get_message()),
-            DesiredValue::Frequency => FlowStatus::Step(self.get_message()),
-            DesiredValue::StartMonth => FlowStatus::Step(self.get_message()),
-            DesiredValue::StartDay => FlowStatus::Step(self.get_message()),
-            DesiredValue::StartTime => FlowStatus::Step(self.get_message()),
-            DesiredValue::Chat => FlowStatus::Step(self.get_message()),
-            DesiredValue::HasToken => FlowStatus::Step(self.get_message()),
-            DesiredValue::Token => match self.has_token {
-                Some(has_token) => match has_token {
-                    true => FlowStatus::Done,
-                    false => FlowStatus::Step(self.get_message()),
-                },
-                None => FlowStatus::Step(self.get_message()),
-            },
-            DesiredValue::None => FlowStatus::Done,
+    /// Returns to the previous step. Entered values are kept, except the last
+    /// message or chat when going back to re-enter it.
+    pub fn move_to_previous_step(&mut self) -> bool {
+        let previous = match self.get_previous_step() {
+            Some(previous) => previous,
+            None => return false,
+        };
+
+        match (&self.desired_value, &previous) {
+            // kept until another message replaces it
+            (DesiredValue::AnotherMessage, DesiredValue::Message) => self.replacing_message = true,
+            (DesiredValue::Message, DesiredValue::AnotherMessage) => self.replacing_message = false,
+            (DesiredValue::AnotherChat, DesiredValue::Chat) => {
+                self.chats.pop();
+            }
+            (_, DesiredValue::EditField) => self.editing = None,
+            _ => {}
         }
+
+        self.desired_value = previous;
+        true
     }
 
-    fn get_message(&self) -> Coorespondance {
+    fn get_step_message(&self) -> Coorespondance {
         match self.desired_value {
             DesiredValue::Message => Coorespondance {
+                back: false,
                 message: "What message or media would you like sent? Send it to me now.".to_string(),
                 option_type: OptionType::Media,
             },
+            DesiredValue::AnotherMessage => Coorespondance {
+                back: false,
+                message: format!(
+                    "Got it, that's message #{}. Would you like to add another message to rotate through?",
+                    self.messages.len()
+                ),
+                option_type: OptionType::YesNo,
+            },
+            DesiredValue::Selection => Coorespondance {
+                back: false,
+                message: "How should I pick the message each time?".to_string(),
+                option_type: OptionType::Options(vec![
+                    vec!["In order".to_owned()],
+                    vec!["Random".to_owned()],
+                    vec!["Random, no repeats".to_owned()],
+                ]),
+            },
             DesiredValue::Frequency => Coorespondance {
-                message: "How often would you like this sent?".to_string(),
+                back: false,
+                message: match self.typed_date {
+                    true => format!(
+                        "Got it, {}. How often would you like this sent? Pick one below.",
+                        self.get_typed_date_display()
+                    ),
+                    false => "How often would you like this sent? Pick one below, or describe it, e.g. \"every monday at 9am\" or \"on the 1st of every month at noon\".".to_string(),
+                },
                 option_type: OptionType::Options(vec![vec![
                     "Daily".to_owned(),
                     "Weekly".to_owned(),
                     "Biweekly".to_owned(),
                     "Monthly".to_owned(),
                     "Yearly".to_owned(),
+                    "Other".to_owned(),
                 ]]),
             },
+            DesiredValue::Interval => Coorespondance {
+                back: false,
+                message: "How many days, weeks or months apart? E.g. \"3 weeks\" or \"10 days\". Counting starts from the date you pick next.".to_string(),
+                option_type: OptionType::Media,
+            },
+            DesiredValue::MonthlyRule => Coorespondance {
+                back: false,
+                message: "Which day of the month? Pick one below, or type e.g. \"second tuesday\".".to_string(),
+                option_type: OptionType::Options(get_option_monthly_rules()),
+            },
             DesiredValue::StartMonth => Coorespondance {
-                message: "Now I'll need a date, for the first message. All future dates will be based off of this date. \n\nPlease select your scheduled month.".to_string(),
-                option_type: OptionType::Options(get_option_months()),
+                back: false,
+                message: "Now I'll need a date, for the first message. All future dates will be based off of this date. \n\nPlease pick it from the calendar, or type the name of the month.".to_string(),
+                option_type: OptionType::Date,
             },
             DesiredValue::StartDay => Coorespondance {
+                back: false,
                 option_type: OptionType::Options(get_option_days(
                     self.first_execution_month.as_ref().unwrap(),
+                    get_start_year(self),
                 )),
                 message: "Please select a day of month".to_string(),
             },
+            DesiredValue::MissingDay => Coorespondance {
+                back: false,
+                message: format!(
+                    "Not every month has a {}. What should happen in the months without it?",
+                    match self.schedule {
+                        Some(Schedule::Yearly) => "February 29".to_owned(),
+                        _ => format!("day {}", self.first_execution_day.as_ref().unwrap()),
+                    }
+                ),
+                option_type: OptionType::Options(vec![
+                    vec![get_missing_day_display(MissingDay::Skip)],
+                    vec![get_missing_day_display(MissingDay::LastDay)],
+                    vec![get_missing_day_display(MissingDay::NextDay)],
+                ]),
+            },
             DesiredValue::StartTime => Coorespondance {
-                message: "What time should the first message be sent?".to_string(),
-                option_type: OptionType::Time,
+                back: false,
+                message: "What time should it be sent? Times are in UTC. Pick as many times as you like, then press Done. You can also type e.g. \"8:00, 12:00, 18:00\" or \"every 2 hours from 8:00 to 18:00\".".to_string(),
+                option_type: OptionType::Time(self.get_picked_times()),
+            },
+            DesiredValue::ConfirmSchedule => Coorespondance {
+                back: false,
+                message: match (&self.schedule, &self.first_execution_day) {
+                    (Some(Schedule::Daily), _) | (_, None) => {
+                        format!("I read that as: {}. Is that right?", self.get_schedule_display())
+                    }
+                    _ => format!(
+                        "I read that as: {}, first on {}. Is that right?",
+                        self.get_schedule_display(),
+                        get_first_execution_date(self).format("%a %-d %b %Y")
+                    ),
+                },
+                option_type: OptionType::YesNo,
+            },
+            DesiredValue::Limits => Coorespondance {
+                back: false,
+                message: "Should this only run for a while? Pick a limit to set, or Done to carry on.".to_string(),
+                option_type: OptionType::Options(vec![
+                    vec!["Start date".to_owned(), "End date".to_owned()],
+                    vec!["Number of deliveries".to_owned()],
+                    vec![
+                        match self.skip_weekends {
+                            true => "Send on weekends".to_owned(),
+                            false => "Skip weekends".to_owned(),
+                        },
+                        "Skip dates".to_owned(),
+                    ],
+                    [
+                        (CatchUp::Once, "Send missed once"),
+                        (CatchUp::All, "Send all missed"),
+                        (CatchUp::Skip, "Skip missed"),
+                    ]
+                    .iter()
+                    .filter(|(catch_up, _)| *catch_up != self.catch_up)
+                    .map(|(_, option)| option.to_string())
+                    .chain(["Grace period".to_owned()])
+                    .collect(),
+                    vec!["Done".to_owned()],
+                ]),
+            },
+            DesiredValue::StartDate => Coorespondance {
+                back: false,
+                message: "Nothing will be sent before this date. Pick it from the calendar, or send \"none\" to remove it.".to_string(),
+                option_type: OptionType::Date,
+            },
+            DesiredValue::EndDate => Coorespondance {
+                back: false,
+                message: "The last day anything is sent, after which the job is archived. Pick it from the calendar, or send \"none\" to remove it.".to_string(),
+                option_type: OptionType::Date,
+            },
+            DesiredValue::MaxDeliveries => Coorespondance {
+                back: false,
+                message: "How many times should it be sent before it stops? Send a number, or \"none\" to remove the limit.".to_string(),
+                option_type: OptionType::Media,
+            },
+            DesiredValue::GraceMinutes => Coorespondance {
+                back: false,
+                message: format!("Deliveries missed while the scheduler was down are only sent late within this many minutes. Send a number, or \"none\" for the default of {}.", DEFAULT_GRACE_MINUTES),
+                option_type: OptionType::Media,
+            },
+            DesiredValue::SkipDates => Coorespondance {
+                back: false,
+                message: "Which days should be skipped? Send the dates, e.g. \"2026-12-24 2026-12-31\", or send me a holiday calendar (.ics) as a file. Send \"none\" to clear them.".to_string(),
+                option_type: OptionType::Media,
             },
             DesiredValue::Chat => Coorespondance {
+                back: false,
                 message:
-                    "Please \"mention\" the chat or channel where you would like the message posted."
+                    "Where would you like the message posted? Pick the chat with the button below, forward me a post from it, \"mention\" it, or send its numeric id. For a forum topic, send the t.me link of a message in it instead."
                         .to_string(),
-                option_type: OptionType::Media,
+                option_type: OptionType::Chat,
+            },
+            DesiredValue::AnotherChat => Coorespondance {
+                back: false,
+                message: format!(
+                    "Added {}. Would you like to post to another chat or channel as well?",
+                    self.chats.last().unwrap()
+                ),
+                option_type: OptionType::YesNo,
             },
             DesiredValue::HasToken => Coorespondance {
+                back: false,
                 message: "Would you like this sent by your own custom bot?".to_string(),
                 option_type: OptionType::YesNo,
             },
             DesiredValue::Token => Coorespondance {
+                back: false,
                 message: "Please provide the bot token.".to_string(),
                 option_type: OptionType::Media,
             },
-            DesiredValue::None => Coorespondance {
+            DesiredValue::Confirm => Coorespondance {
+                back: false,
+                message: self.get_summary(),
+                option_type: OptionType::Options(vec![
+                    vec!["Confirm".to_owned()],
+                    vec!["Edit".to_owned(), "Cancel".to_owned()],
+                ]),
+            },
+            DesiredValue::EditField => Coorespondance {
+                back: false,
+                message: "What would you like to change?".to_string(),
+                option_type: OptionType::Options(vec![
+                    vec!["Messages".to_owned(), "Schedule".to_owned()],
+                    vec!["Chats".to_owned(), "Bot".to_owned()],
+                ]),
+            },
+            DesiredValue::Import => Coorespondance {
+                back: false,
+                option_type: OptionType::Media,
+                message: "Send me a backup made with /export to restore its jobs, or an .ics calendar file whose repeating events become jobs.".to_owned(),
+            },
+            DesiredValue::ImportChat => Coorespondance {
+                back: false,
+                option_type: OptionType::Chat,
+                message: "Which chat should the imported jobs post to? Pick it with the button below, forward me a post from it, or send its @name or numeric id.".to_owned(),
+            },
+            DesiredValue::ManagedChat | DesiredValue::None => Coorespondance {
+                back: false,
                 option_type: OptionType::None,
                 message: "Your message is scheduled. Thank you!".to_owned(),
             },
@@ -239,8 +1761,10 @@ impl ConfigInProgress {
 
 pub enum OptionType {
     Options(Vec<Vec<String>>),
+    Chat,
     Media,
-    Time,
+    // with the times already picked
+    Time(Vec<String>),
     Date,
     YesNo,
     None,
@@ -249,8 +1773,13 @@ pub enum OptionType {
 pub struct Coorespondance {
     pub option_type: OptionType,
     pub message: String,
+    // whether to offer a button returning to the previous step
+    pub back: bool,
 }
 
+/// Text of the button that returns to the previous wizard step.
+pub const BACK: &str = "« Back";
+
 fn load_input(
     state: &mut Option<ConfigInProgress>,
     message: &Message,
@@ -281,6 +1810,9 @@ fn load_input(
         Message::Document(id) => match state {
             Some(state) => match state.desired_value {
                 DesiredValue::Message => Ok(UserInput::Media(Message::Document(id.to_string()))),
+                DesiredValue::SkipDates | DesiredValue::Import => {
+                    Ok(UserInput::Calendar(id.to_string()))
+                }
                 _ => get_error(&state.desired_value),
             },
             None => Err("Hi! To get started, use /start.".to_string()),
@@ -303,26 +1835,62 @@ fn load_text_input(
         return load_command(message);
     }
 
+    if message == BACK && state.is_some() {
+        return Ok(UserInput::Back);
+    }
+
     match state {
         Some(state) => match state.desired_value {
-            DesiredValue::Message => Ok(UserInput::Message(message.to_owned())),
-            DesiredValue::Chat => {
+            DesiredValue::Message => match validate_template(message) {
+                Ok(()) => Ok(UserInput::Message(message.to_owned())),
+                Err(error) => Err(error),
+            },
+            DesiredValue::AnotherMessage => Ok(UserInput::YesNo(message.to_lowercase() == "yes")),
+            DesiredValue::Selection => {
+                UserInput::parse_selection(message).or_else(|_| load_typed_schedule(state, message))
+            }
+            DesiredValue::Chat | DesiredValue::ManagedChat | DesiredValue::ImportChat => {
                 // note - bad links would have since been turned into INVALID - which does not start with an '@' ;)
-                if message.starts_with('@') {
-                    Ok(UserInput::Message(message.to_owned()))
+                if message.starts_with('@') || message.trim().parse::<i64>().is_ok() {
+                    Ok(UserInput::Chat(Target::new(message.trim().to_owned())))
                 } else {
-                    get_error(&state.desired_value)
+                    match (parse_chat_link(message), &state.desired_value) {
+                        (Some(target), _) => Ok(UserInput::Chat(target)),
+                        (None, DesiredValue::Chat) => load_typed_schedule(state, message),
+                        (None, _) => get_error(&state.desired_value),
+                    }
                 }
             }
             DesiredValue::Frequency => {
                 let parse = UserInput::parse_frequency(message);
                 if parse.is_err() {
-                    get_error(&state.desired_value)
+                    load_typed_schedule(state, message)
                 } else {
                     Ok(parse.unwrap())
                 }
             }
+            DesiredValue::Interval => match parse_interval(message) {
+                Some((every, unit)) => Ok(UserInput::Frequency(Schedule::Interval(every, unit))),
+                None => load_typed_schedule(state, message),
+            },
+            DesiredValue::MonthlyRule => match parse_monthly_rule(message) {
+                Some(rule) => {
+                    state.monthly_rule = Some(rule);
+                    Ok(UserInput::Message(message.to_owned()))
+                }
+                None => load_typed_schedule(state, message),
+            },
             DesiredValue::StartMonth => {
+                // a date picked from the calendar answers the day as well
+                if let Ok(date) = NaiveDate::parse_from_str(message, "%Y-%m-%d") {
+                    state.first_execution_year = Some(date.year());
+                    state.first_execution_month = Some(string_from_month(
+                        get_month_from_int(date.month() as i32).unwrap(),
+                    ));
+                    state.first_execution_day = Some(date.day().to_string());
+                    return Ok(UserInput::Date(message.to_owned()));
+                }
+
                 let parse: Result<i32, ()> = match message.to_lowercase().as_str() {
                     "january" => Ok(1),
                     "february" => Ok(2),
@@ -342,13 +1910,17 @@ fn load_text_input(
                 match parse {
                     Ok(_) => {
                         state.first_execution_month = Some(message.to_owned());
+                        state.first_execution_year = None;
                         Ok(UserInput::Message(message.to_owned()))
                     }
-                    Err(()) => get_error(&state.desired_value),
+                    Err(()) => load_typed_schedule(state, message),
                 }
             }
             DesiredValue::StartDay => {
-                let days = get_days_by_month(&state.first_execution_month.as_ref().unwrap());
+                let days = get_days_by_month(
+                    state.first_execution_month.as_ref().unwrap(),
+                    get_start_year(state),
+                );
 
                 match message.parse::<i32>() {
                     Ok(number) => {
@@ -359,64 +1931,334 @@ fn load_text_input(
                             Ok(UserInput::Message(message.to_owned()))
                         }
                     }
-                    Err(_) => get_error(&state.desired_value),
+                    Err(_) => load_typed_schedule(state, message),
                 }
             }
+            DesiredValue::MissingDay => match parse_missing_day(message) {
+                Some(missing_day) => {
+                    state.missing_day = Some(missing_day);
+                    Ok(UserInput::Message(message.to_owned()))
+                }
+                None => load_typed_schedule(state, message),
+            },
             DesiredValue::StartTime => {
-                let time = NaiveTime::parse_from_str(message, "%H:%M");
+                if message.to_lowercase() == "done" {
+                    state.picking_times = false;
+                    return match &state.first_execution_time {
+                        Some(time) => Ok(UserInput::Time(time.to_owned())),
+                        None => Err("Please pick at least one time first.".to_owned()),
+                    };
+                }
+
+                // a picked time is added, or removed when picked again
+                if let Ok(time) = NaiveTime::parse_from_str(message, "%H:%M") {
+                    let mut times = state
+                        .get_picked_times()
+                        .iter()
+                        .filter_map(|picked| NaiveTime::parse_from_str(picked, "%H:%M").ok())
+                        .collect::<Vec<NaiveTime>>();
+                    match times.iter().position(|picked| *picked == time) {
+                        Some(index) => {
+                            times.remove(index);
+                        }
+                        None => times.push(time),
+                    }
+
+                    state.picking_times = true;
+                    return Ok(UserInput::Time(format_times(&times)));
+                }
 
-                match time {
-                    Err(_) => get_error(&state.desired_value),
-                    Ok(_) => Ok(UserInput::Time(message.to_owned())),
+                match parse_times_text(message) {
+                    Some(times) => {
+                        state.picking_times = false;
+                        Ok(UserInput::Time(times))
+                    }
+                    None => load_typed_schedule(state, message),
+                }
+            }
+            // another schedule typed instead replaces the one read
+            DesiredValue::ConfirmSchedule => match message.to_lowercase().as_str() {
+                "yes" => Ok(UserInput::YesNo(true)),
+                _ => load_typed_schedule(state, message).or(Ok(UserInput::YesNo(false))),
+            },
+            DesiredValue::Limits => match message.to_lowercase().as_str() {
+                "start date" => Ok(Some(Limit::StartDate)),
+                "end date" => Ok(Some(Limit::EndDate)),
+                "number of deliveries" => Ok(Some(Limit::MaxDeliveries)),
+                "skip weekends" => {
+                    state.skip_weekends = true;
+                    Ok(Some(Limit::Weekends))
+                }
+                "send on weekends" => {
+                    state.skip_weekends = false;
+                    Ok(Some(Limit::Weekends))
+                }
+                "skip dates" => Ok(Some(Limit::SkipDates)),
+                "send missed once" | "send all missed" | "skip missed" => {
+                    state.catch_up = match message.to_lowercase().as_str() {
+                        "send all missed" => CatchUp::All,
+                        "skip missed" => CatchUp::Skip,
+                        _ => CatchUp::Once,
+                    };
+                    Ok(Some(Limit::CatchUp))
+                }
+                "grace period" => Ok(Some(Limit::GraceMinutes)),
+                "done" => Ok(None),
+                _ => Err(()),
+            }
+            .map(|limit| {
+                state.limit = limit;
+                UserInput::Message(message.to_owned())
+            })
+            .or_else(|()| load_typed_schedule(state, message)),
+            DesiredValue::StartDate | DesiredValue::EndDate => {
+                match load_limit_date(state, message) {
+                    Ok(()) => Ok(UserInput::Message(message.to_owned())),
+                    // a date that was read but doesn't fit stays an error
+                    Err(error) if NaiveDate::parse_from_str(message.trim(), "%Y-%m-%d").is_ok() => {
+                        Err(error)
+                    }
+                    Err(error) => load_typed_schedule(state, message).map_err(|_| error),
                 }
             }
+            DesiredValue::SkipDates => match load_skip_dates(state, message) {
+                Ok(()) => Ok(UserInput::Message(message.to_owned())),
+                Err(error) => load_typed_schedule(state, message).map_err(|_| error),
+            },
+            DesiredValue::MaxDeliveries => match message.to_lowercase().trim() {
+                "none" => {
+                    state.max_deliveries = None;
+                    Ok(UserInput::Message(message.to_owned()))
+                }
+                number => match number.parse::<u32>() {
+                    Ok(max) if max > 0 => {
+                        state.max_deliveries = Some(max);
+                        Ok(UserInput::Message(message.to_owned()))
+                    }
+                    Ok(_) => get_error(&state.desired_value),
+                    Err(_) => load_typed_schedule(state, message),
+                },
+            },
+            DesiredValue::GraceMinutes => match message.to_lowercase().trim() {
+                "none" => {
+                    state.grace_minutes = None;
+                    Ok(UserInput::Message(message.to_owned()))
+                }
+                minutes => match minutes.parse::<u32>() {
+                    Ok(grace) => {
+                        state.grace_minutes = Some(grace);
+                        Ok(UserInput::Message(message.to_owned()))
+                    }
+                    Err(_) => load_typed_schedule(state, message),
+                },
+            },
+            DesiredValue::AnotherChat => Ok(UserInput::YesNo(message.to_lowercase() == "yes")),
             DesiredValue::HasToken => Ok(UserInput::YesNo(message.to_lowercase() == "yes")),
-            DesiredValue::Token => Ok(UserInput::Message(message.to_owned())),
+            DesiredValue::Token => match is_bot_token(message.trim()) {
+                true => Ok(UserInput::Message(message.trim().to_owned())),
+                false => get_error(&state.desired_value),
+            },
+            DesiredValue::Confirm => match message.to_lowercase().as_str() {
+                "confirm" => Ok(UserInput::YesNo(true)),
+                "edit" => Ok(UserInput::YesNo(false)),
+                "cancel" => Ok(UserInput::Command(Command::Cancel)),
+                _ => load_typed_schedule(state, message),
+            },
+            DesiredValue::EditField => {
+                UserInput::parse_field(message).or_else(|_| load_typed_schedule(state, message))
+            }
+            // the calendar is sent as a file
+            DesiredValue::Import => get_error(&state.desired_value),
             DesiredValue::None => Ok(UserInput::Message(message.to_owned())),
         },
         None => Err("Hi! To get started, use /start.".to_string()),
     }
 }
 
+/// Stores the start or end date being asked for. The end can't be before
+/// today or the start.
+fn load_limit_date(state: &mut ConfigInProgress, message: &str) -> Result<(), String> {
+    let date = match message.to_lowercase().trim() {
+        "none" => None,
+        text => match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            Ok(date) => Some(date),
+            Err(_) => return get_error(&state.desired_value).map(|_| ()),
+        },
+    };
+
+    let (start, end) = match state.desired_value {
+        DesiredValue::StartDate => (date, state.end_date),
+        _ => (state.start_date, date),
+    };
+
+    if let Some(end) = end {
+        if end < Utc::now().date_naive() {
+            return Err("The end date has already passed.".to_owned());
+        }
+        if start.is_some_and(|start| end < start) {
+            return Err("The end date can't be before the start date.".to_owned());
+        }
+    }
+
+    state.start_date = start;
+    state.end_date = end;
+    Ok(())
+}
+
+/// Adds the dates to skip, written out or as the text of an .ics calendar.
+/// Dates that have passed are left out.
+fn load_skip_dates(state: &mut ConfigInProgress, text: &str) -> Result<(), String> {
+    if text.trim().to_lowercase() == "none" {
+        state.skip_dates.clear();
+        return Ok(());
+    }
+
+    let dates = match text.contains("BEGIN:VCALENDAR") {
+        true => parse_ics_dates(text)?,
+        false => text
+            .split([' ', ',', '\n'])
+            .filter(|date| !date.trim().is_empty())
+            .map(|date| {
+                NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                    .map_err(|_| format!("I couldn't read \"{}\" as a date.", date.trim()))
+            })
+            .collect::<Result<Vec<NaiveDate>, String>>()?,
+    };
+
+    let today = Utc::now().date_naive();
+    state
+        .skip_dates
+        .extend(dates.into_iter().filter(|date| *date >= today));
+    state.skip_dates.sort();
+    state.skip_dates.dedup();
+
+    Ok(())
+}
+
+/// Falls back to reading a schedule written out, like "every friday at 18:00"
+/// or "tomorrow 18:30", at the steps where it can't be taken for an answer.
+fn load_typed_schedule(state: &ConfigInProgress, message: &str) -> Result<UserInput, String> {
+    match parse_schedule(message, &Utc::now().naive_utc()) {
+        Some(parsed) => Ok(UserInput::Schedule(parsed)),
+        None => get_error(&state.desired_value),
+    }
+}
+
 fn load_command(message: &String) -> Result<UserInput, String> {
     let words = message.split(" ").collect::<Vec<&str>>();
-    let command = words.get(0).unwrap();
+    // in groups commands may be addressed as /command@botname
+    let command = words.get(0).unwrap().split('@').next().unwrap();
 
     match command.to_lowercase().as_str() {
         "/start" => Ok(UserInput::Command(Command::Start)),
         "/cancel" => Ok(UserInput::Command(Command::Cancel)),
         "/list" => Ok(UserInput::Command(Command::List)),
+        "/import" => Ok(UserInput::Command(Command::Import)),
+        "/export" => match words.get(1).map(|word| word.to_lowercase()).as_deref() {
+            None | Some("yaml") => Ok(UserInput::Command(Command::Export(ExportFormat::Yaml))),
+            Some("json") => Ok(UserInput::Command(Command::Export(ExportFormat::Json))),
+            Some("ics") => Ok(UserInput::Command(Command::Export(ExportFormat::Ics))),
+            _ => Err("Use /export for a backup of your jobs, /export json for the same as JSON, or /export ics for a calendar file.".to_owned()),
+        },
         "/view" => match words.get(1) {
-            Some(val) => match val.parse::<i32>() {
-                Ok(_) => Ok(UserInput::Command(Command::View(val.to_string()))),
-                Err(_) => Err(
-                    "Please provide a valid number cooresponding to the job you'd like to view."
-                        .to_owned(),
-                ),
+            Some(val) => match is_job_reference(val) {
+                true => Ok(UserInput::Command(Command::View(val.to_string()))),
+                false => {
+                    Err("Please provide the number or id of the job you'd like to view.".to_owned())
+                }
             },
             None => Err("View requires exactly one argument".to_owned()),
         },
         "/delete" => match words.get(1) {
-            Some(val) => match val.parse::<i32>() {
-                Ok(_) => Ok(UserInput::Command(Command::Delete(val.to_string()))),
-                Err(_) => Err(
-                    "Please provide a valid number cooresponding to the job you'd like to delete."
-                        .to_owned(),
+            Some(val) => match is_job_reference(val) {
+                true => Ok(UserInput::Command(Command::Delete(val.to_string()))),
+                false => Err(
+                    "Please provide the number or id of the job you'd like to delete.".to_owned(),
                 ),
             },
             None => Err("Delete requires exactly one argument".to_owned()),
         },
+        "/jobs" => {
+            let reference = match words.get(2) {
+                Some(val) if is_job_reference(val) => Some(val.to_string()),
+                Some(_) => return Err("Please provide the number or id of the job.".to_owned()),
+                None => None,
+            };
+
+            let action = match (words.get(1).map(|word| word.to_lowercase()), reference) {
+                (None, _) => JobsAction::List,
+                (Some(word), None) if word == "audit" => JobsAction::Audit,
+                (Some(word), Some(reference)) => match word.as_str() {
+                    "view" => JobsAction::View(reference),
+                    "delete" => JobsAction::Delete(reference),
+                    "pause" => JobsAction::Pause(reference),
+                    "resume" => JobsAction::Resume(reference),
+                    "edit" => JobsAction::Edit(reference),
+                    _ => return Err(get_jobs_usage()),
+                },
+                _ => return Err(get_jobs_usage()),
+            };
+
+            Ok(UserInput::Command(Command::Jobs(action)))
+        }
         _ => Err("Invalid command.".to_owned()),
     }
 }
-pub fn accept_incoming_message(u_id: &String, message: &Message) -> FlowStatus {
+/// Lets the wizard look up chats on the messaging platform.
+pub trait ChatDirectory {
+    /// Resolves the chat of `target` and checks that both the bot and the user
+    /// `u_id` are allowed to post there, the user as an administrator.
+    /// Returns the target with its canonical chat id, or a message for the user.
+    fn verify_chat(&self, target: Target, u_id: &String) -> Result<Target, String>;
+
+    /// Whether the user `u_id` currently administers the chat.
+    fn is_chat_admin(&self, chat_id: &String, u_id: &String) -> bool;
+
+    /// The text of a file the user sent, None if it can't be downloaded.
+    fn read_document(&self, file_id: &String) -> Option<String>;
+
+    /// The @username of the bot with `token`, or of this bot without one.
+    fn get_bot_name(&self, token: Option<&String>) -> Option<String>;
+}
+
+/// `forwarded_from` is the id of the chat a forwarded message originally came from.
+pub fn accept_incoming_message(
+    u_id: &String,
+    message: &Message,
+    forwarded_from: Option<String>,
+    chats: &dyn ChatDirectory,
+) -> FlowStatus {
     // first thing we have to do is stick this into an enum.
     let mut state = get_state(&u_id);
 
-    let validate = load_input(&mut state, &message);
+    let validate = match (&state, forwarded_from) {
+        // forwarding a post is one way of picking the chat
+        (Some(in_progress), Some(chat_id))
+            if matches!(
+                in_progress.desired_value,
+                DesiredValue::Chat | DesiredValue::ManagedChat | DesiredValue::ImportChat
+            ) =>
+        {
+            Ok(UserInput::Chat(Target::new(chat_id)))
+        }
+        _ => load_input(&mut state, &message),
+    };
+
+    let validate = match validate {
+        Ok(UserInput::Chat(target)) => chats.verify_chat(target, u_id).map(UserInput::Chat),
+        Ok(UserInput::Calendar(file_id)) => match (chats.read_document(&file_id), state.as_mut()) {
+            (Some(text), Some(in_progress)) => match in_progress.desired_value {
+                DesiredValue::Import => load_import(in_progress, &text),
+                _ => load_skip_dates(in_progress, &text),
+            }
+            .map(|()| UserInput::Calendar(file_id)),
+            _ => Err("I couldn't download that file, please send it again.".to_owned()),
+        },
+        other => other,
+    };
 
     match validate {
-        Ok(input) => process_incoming_message(u_id, input, &mut state),
+        Ok(input) => process_incoming_message(u_id, input, &mut state, chats),
         Err(message) => FlowStatus::Error {
             message,
             desired_value: match state {
@@ -431,23 +2273,100 @@ fn process_incoming_message(
     u_id: &String,
     message: UserInput,
     state: &mut Option<ConfigInProgress>,
+    chats: &dyn ChatDirectory,
 ) -> FlowStatus {
     match message {
+        UserInput::Command(Command::Jobs(action)) => process_jobs(u_id, action, chats),
         UserInput::Command(com) => com.execute(u_id),
+        UserInput::Back => match state {
+            Some(state) => {
+                if state.move_to_previous_step() {
+                    save_state(u_id, state);
+                    state.get_flow_status()
+                } else {
+                    FlowStatus::Error {
+                        message: "There is no step to go back to.".to_owned(),
+                        desired_value: DesiredValue::None,
+                    }
+                }
+            }
+            None => FlowStatus::Error {
+                message: "There is no step to go back to.".to_owned(),
+                desired_value: DesiredValue::None,
+            },
+        },
+        UserInput::Schedule(parsed) => match state {
+            Some(state) => {
+                apply_typed_schedule(state, parsed);
+                save_state(u_id, state);
+                state.get_flow_status()
+            }
+            None => FlowStatus::Error {
+                message: "I'm confused... let's just start over".to_owned(),
+                desired_value: DesiredValue::Message,
+            },
+        },
         _ => match state {
+            Some(state) if matches!(state.desired_value, DesiredValue::ManagedChat) => {
+                process_managed_chat(u_id, message, chats)
+            }
+            Some(state) if matches!(state.desired_value, DesiredValue::Import) => {
+                process_import(u_id, state, chats)
+            }
+            Some(state) if matches!(state.desired_value, DesiredValue::ImportChat) => {
+                process_import_chat(u_id, state, message)
+            }
             Some(state) => {
                 let closed = match state.desired_value {
                     DesiredValue::Message => process_desired_message(state, message),
-                    DesiredValue::Frequency => process_frequency(state, message),
+                    DesiredValue::AnotherMessage => process_another_message(state, message),
+                    DesiredValue::Selection => process_selection(state, message),
+                    DesiredValue::Frequency | DesiredValue::Interval => {
+                        process_frequency(state, message)
+                    }
+                    // stored when the answer was parsed
+                    DesiredValue::MonthlyRule => false,
                     DesiredValue::StartDay => process_month_day(state, message),
                     DesiredValue::StartMonth => process_month(state, message),
                     DesiredValue::StartTime => process_time(state, message),
+                    // stored when the answer was parsed
+                    DesiredValue::MissingDay => false,
+                    DesiredValue::ConfirmSchedule => process_confirm_schedule(state, message),
+                    // stored when the answer was parsed
+                    DesiredValue::Limits
+                    | DesiredValue::StartDate
+                    | DesiredValue::EndDate
+                    | DesiredValue::MaxDeliveries
+                    | DesiredValue::SkipDates
+                    | DesiredValue::GraceMinutes => false,
                     DesiredValue::Chat => process_chat(state, message),
-                    DesiredValue::HasToken => process_has_token(state, message, u_id),
-                    DesiredValue::Token => process_token(state, message, u_id),
-                    DesiredValue::None => true,
+                    DesiredValue::AnotherChat => process_another_chat(state, message),
+                    DesiredValue::HasToken => process_has_token(state, message),
+                    DesiredValue::Token => process_token(state, message),
+                    DesiredValue::Confirm => match process_confirm(state, message, u_id) {
+                        Ok(closed) => closed,
+                        // the answers are kept, so confirming can be tried again
+                        Err(message) => {
+                            return FlowStatus::Error {
+                                message,
+                                desired_value: DesiredValue::Confirm,
+                            }
+                        }
+                    },
+                    DesiredValue::EditField => process_edit_field(state, message),
+                    DesiredValue::ManagedChat
+                    | DesiredValue::Import
+                    | DesiredValue::ImportChat
+                    | DesiredValue::None => true,
                 };
 
+                if matches!(
+                    state.desired_value,
+                    DesiredValue::HasToken | DesiredValue::Token
+                ) {
+                    state.bot_name = chats.get_bot_name(state.token.as_ref());
+                }
+
                 if !closed {
                     state.move_to_next_step();
                     save_state(u_id, state);
@@ -465,12 +2384,19 @@ fn process_incoming_message(
 }
 
 fn process_desired_message(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    if config_in_progress.replacing_message {
+        config_in_progress.replacing_message = false;
+        config_in_progress.messages.pop();
+    }
+
     match message {
         UserInput::Message(desired_message) => {
-            config_in_progress.message = Some(Message::Message(desired_message));
+            config_in_progress
+                .messages
+                .push(Message::Message(desired_message));
         }
         UserInput::Media(msg) => {
-            config_in_progress.message = Some(msg);
+            config_in_progress.messages.push(msg);
         }
         _ => panic!("Unsupported Input type"),
     }
@@ -478,6 +2404,24 @@ fn process_desired_message(config_in_progress: &mut ConfigInProgress, message: U
     false
 }
 
+fn process_another_message(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::YesNo(answer) => config_in_progress.another_message = Some(answer),
+        _ => panic!("Unsupported Input type"),
+    }
+
+    false
+}
+
+fn process_selection(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::Selection(selection) => config_in_progress.selection = Some(selection),
+        _ => panic!("Unsupported Input type"),
+    }
+
+    false
+}
+
 fn process_frequency<'a>(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
     match message {
         UserInput::Frequency(frequency) => config_in_progress.schedule = Some(frequency),
@@ -501,6 +2445,8 @@ fn process_month_day(config_in_progress: &mut ConfigInProgress, message: UserInp
 fn process_month(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
     match message {
         UserInput::Message(date) => config_in_progress.first_execution_month = Some(date),
+        // month and day were taken from the date when it was parsed
+        UserInput::Date(_) => {}
         _ => panic!("Unsupported Input type"),
     }
 
@@ -509,6 +2455,8 @@ fn process_month(config_in_progress: &mut ConfigInProgress, message: UserInput)
 
 fn process_time(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
     match message {
+        // nothing is left when the only time picked is taken away
+        UserInput::Time(time) if time.is_empty() => config_in_progress.first_execution_time = None,
         UserInput::Time(time) => config_in_progress.first_execution_time = Some(time),
         _ => panic!("Unsupported Input type"),
     }
@@ -516,53 +2464,232 @@ fn process_time(config_in_progress: &mut ConfigInProgress, message: UserInput) -
     false
 }
 
+/// Takes what was understood from a typed schedule and asks to confirm it.
+fn apply_typed_schedule(config_in_progress: &mut ConfigInProgress, parsed: ParsedSchedule) {
+    // typed at the summary, it only changes the schedule
+    if matches!(
+        config_in_progress.desired_value,
+        DesiredValue::Confirm | DesiredValue::EditField
+    ) {
+        config_in_progress.editing = Some(Field::Schedule);
+    }
+
+    let frequency = parsed.schedule.is_some();
+    if let Some(schedule) = parsed.schedule {
+        config_in_progress.schedule = Some(schedule);
+        config_in_progress.monthly_rule = None;
+    }
+
+    if let Some(date) = parsed.date {
+        config_in_progress.set_first_execution_date(date);
+    }
+
+    if let Some(time) = parsed.time {
+        config_in_progress.first_execution_time = Some(time.format("%H:%M").to_string());
+    }
+
+    config_in_progress.schedule_confirmed = None;
+    // without a frequency yet, it's confirmed once one is picked
+    match frequency || config_in_progress.schedule.is_some() {
+        true => {
+            config_in_progress.typed_date = false;
+            config_in_progress.desired_value = DesiredValue::ConfirmSchedule;
+        }
+        false => {
+            config_in_progress.typed_date = true;
+            config_in_progress.desired_value = DesiredValue::Frequency;
+        }
+    }
+}
+
+fn process_confirm_schedule(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::YesNo(answer) => {
+            config_in_progress.schedule_confirmed = Some(answer);
+            config_in_progress.typed_date = false;
+        }
+        _ => panic!("Unsupported Input type"),
+    }
+
+    false
+}
+
 fn process_chat(confing_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
     match message {
-        UserInput::Message(string) => confing_in_progress.chat_id = Some(string),
+        UserInput::Chat(target) => {
+            if !confing_in_progress.chats.contains(&target) {
+                confing_in_progress.chats.push(target)
+            }
+        }
+        _ => panic!("Unsupported Input Type"),
+    }
+
+    false
+}
+
+/// Reads a t.me link to a chat or to a message in it. Only links to messages in
+/// a topic keep its thread id, e.g. t.me/c/1234/5/678 is in topic 5 of the
+/// private supergroup -1001234, while t.me/c/1234/678 is just the chat.
+fn parse_chat_link(link: &str) -> Option<Target> {
+    let link = link.trim();
+    let link = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+        .unwrap_or(link);
+    let link = link
+        .strip_prefix("t.me/")
+        .or_else(|| link.strip_prefix("telegram.me/"))?;
+
+    let (path, query) = match link.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (link, None),
+    };
+
+    // links to replies inside a topic carry it as ?thread=
+    let query_thread = query.and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("thread="))
+            .and_then(|thread| thread.parse::<i64>().ok())
+    });
+
+    let parts = path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>();
+
+    let (chat_id, rest) = match parts.as_slice() {
+        ["c", id, rest @ ..] => {
+            id.parse::<i64>().ok()?;
+            (format!("-100{}", id), rest)
+        }
+        [username, rest @ ..] => {
+            if username.starts_with('+') || *username == "joinchat" {
+                // invite links don't identify the chat
+                return None;
+            }
+            (format!("@{}", username), rest)
+        }
+        [] => return None,
+    };
+
+    // a single number is a message in a channel or a group without topics
+    let thread_id = match (query_thread, rest) {
+        (Some(thread), _) => Some(thread),
+        (None, [thread, _message]) => Some(thread.parse::<i64>().ok()?),
+        (None, _) => None,
+    };
+
+    match thread_id {
+        Some(thread_id) => Some(Target::in_thread(chat_id, thread_id)),
+        None => Some(Target::new(chat_id)),
+    }
+}
+
+fn process_another_chat(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::YesNo(answer) => config_in_progress.another_chat = Some(answer),
+        _ => panic!("Unsupported Input Type"),
+    }
+
+    false
+}
+
+fn process_has_token(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::YesNo(answer) => {
+            config_in_progress.has_token = Some(answer);
+
+            if !answer {
+                config_in_progress.token = None;
+            }
+            false
+        }
+
+        _ => panic!("Unsupported Input Type"),
+    }
+}
+
+// "<bot id>:<secret>", as BotFather hands them out
+fn is_bot_token(token: &str) -> bool {
+    match token.split_once(':') {
+        Some((id, secret)) => {
+            !id.is_empty()
+                && id.chars().all(|c| c.is_ascii_digit())
+                && !secret.is_empty()
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        None => false,
+    }
+}
+
+fn process_token(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
+    match message {
+        UserInput::Message(token) => config_in_progress.token = Some(token),
         _ => panic!("Unsupported Input Type"),
     }
 
     false
 }
 
-fn process_has_token(
+fn process_confirm(
     config_in_progress: &mut ConfigInProgress,
     message: UserInput,
     u_id: &String,
-) -> bool {
+) -> Result<bool, String> {
     match message {
-        UserInput::YesNo(answer) => {
-            config_in_progress.has_token = Some(answer);
-
-            if !answer {
-                // flow is now done!
-                close(u_id, config_in_progress);
-                true
-            } else {
-                false
-            }
+        UserInput::YesNo(true) => {
+            // flow is now done!
+            close(u_id, config_in_progress)?;
+            Ok(true)
         }
-
+        UserInput::YesNo(false) => Ok(false),
         _ => panic!("Unsupported Input Type"),
     }
 }
 
-fn process_token(
-    config_in_progress: &mut ConfigInProgress,
-    message: UserInput,
-    u_id: &String,
-) -> bool {
+fn process_edit_field(config_in_progress: &mut ConfigInProgress, message: UserInput) -> bool {
     match message {
-        UserInput::Message(token) => config_in_progress.token = Some(token),
+        UserInput::Field(field) => {
+            // lists are entered again from scratch
+            match field {
+                Field::Messages => {
+                    config_in_progress.messages.clear();
+                    config_in_progress.selection = None;
+                }
+                Field::Chats => config_in_progress.chats.clear(),
+                _ => {}
+            }
+            config_in_progress.editing = Some(field);
+        }
         _ => panic!("Unsupported Input Type"),
     }
 
-    close(u_id, config_in_progress);
-    true
+    false
+}
+
+/// The first `count` times the job being set up would be sent after `now`.
+fn get_next_occurrences(
+    state: &ConfigInProgress,
+    now: &NaiveDateTime,
+    count: usize,
+) -> Vec<NaiveDateTime> {
+    match state.to_schedule() {
+        Some(schedule) => state
+            .to_config()
+            .next_occurrences(&schedule, &now.and_utc(), count)
+            .iter()
+            .map(|occurrence| occurrence.naive_utc())
+            .collect(),
+        None => Vec::new(),
+    }
 }
 
+/// The first date the job is sent on. A month picked by name is the next one
+/// of that name, so a date that has passed this year moves to the next.
 fn get_first_execution_date(state: &ConfigInProgress) -> NaiveDate {
-    let year = Utc::now().year();
     let month = get_month_number(&state.first_execution_month);
     let day = state
         .first_execution_day
@@ -571,67 +2698,99 @@ fn get_first_execution_date(state: &ConfigInProgress) -> NaiveDate {
         .parse::<u32>()
         .unwrap();
 
-    NaiveDate::from_ymd(year, month, day)
+    if let Some(year) = state.first_execution_year {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // february 29th can be a few years off
+    let today = Utc::now().date_naive();
+    (today.year()..today.year() + 8)
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .find(|date| *date >= today)
+        .unwrap()
 }
 
-fn close(u_id: &String, config_in_progress: &mut ConfigInProgress) {
-    delete_state(u_id);
+// year of the next month named like the one picked, for its number of days
+fn get_start_year(state: &ConfigInProgress) -> i32 {
+    if let Some(year) = state.first_execution_year {
+        return year;
+    }
 
-    let config = Config {
-        chat_id: config_in_progress.chat_id.to_owned().unwrap(),
-        message: config_in_progress.message.to_owned().unwrap(),
-    };
+    let today = Utc::now().date_naive();
+    match get_month_number(&state.first_execution_month) < today.month() {
+        true => today.year() + 1,
+        false => today.year(),
+    }
+}
 
-    match config_in_progress.schedule.to_owned().unwrap() {
-        Schedule::Daily => {
-            let schedule = emSchedule::Daily {
-                time: config_in_progress.first_execution_time.to_owned().unwrap(),
-            };
+/// Saves the job. The answers are only dropped once it's saved.
+fn close(u_id: &String, config_in_progress: &mut ConfigInProgress) -> Result<(), String> {
+    let mut config = config_in_progress.to_config();
+    config.owner = Some(u_id.to_owned());
+    let targets = config.targets.to_owned();
 
-            let _ = create_schedule(u_id, config, schedule);
-        }
-        Schedule::Weekly => {
-            let date = get_first_execution_date(config_in_progress);
-            let schedule = emSchedule::Weekly {
-                weekday: date.weekday(),
-                time: config_in_progress.first_execution_time.to_owned().unwrap(),
-            };
+    let schedule = config_in_progress
+        .to_schedule()
+        .ok_or("I couldn't work out the schedule. Please edit it and confirm again.")?;
 
-            let _ = create_schedule(u_id, config, schedule);
-        }
-        Schedule::Biweekly => {
-            let date = get_first_execution_date(config_in_progress);
+    if let Some(path) = &config_in_progress.edited_job {
+        save_edited_job(u_id, path, config, schedule)?;
+        delete_state(u_id);
+        return Ok(());
+    }
 
-            let schedule = emSchedule::BiWeekly {
-                weekday: date.weekday(),
-                time: config_in_progress.first_execution_time.to_owned().unwrap(),
-                odd: date.iso_week().week() % 2 == 0,
-            };
+    if !create_schedule(u_id, config, schedule) {
+        return Err("I couldn't save the job. Please try confirming again.".to_owned());
+    }
 
-            let _ = create_schedule(u_id, config, schedule);
-        }
-        Schedule::Monthly => {
-            let date = get_first_execution_date(config_in_progress);
+    delete_state(u_id);
 
-            let schedule = emSchedule::Monthly {
-                day: date.day() as i32,
-                time: config_in_progress.first_execution_time.to_owned().unwrap(),
-            };
+    for target in targets.iter() {
+        append_audit(&target.chat_id, &format!("user {} created a job", u_id));
+    }
 
-            let _ = create_schedule(u_id, config, schedule);
-        }
-        Schedule::Yearly => {
-            let date = get_first_execution_date(config_in_progress);
+    Ok(())
+}
 
-            let schedule = emSchedule::Yearly {
-                month: get_month_from_int(date.month() as i32).unwrap(),
-                day: date.day() as i32,
-                time: config_in_progress.first_execution_time.to_owned().unwrap(),
-            };
+/// Replaces the job at `path` with its edited version, which keeps its id,
+/// owner and progress. The old one is put back if the new one can't be saved.
+fn save_edited_job(
+    u_id: &String,
+    path: &String,
+    mut config: Config,
+    schedule: emSchedule,
+) -> Result<(), String> {
+    let original = load_config(path)?;
+    let original_schedule =
+        emSchedule::from_file_location(path).ok_or("The job's schedule can't be read.")?;
+    let owner = get_owner(path, &original);
+
+    config.id = original.id.to_owned();
+    config.owner = Some(owner.to_owned());
+    config.timezone = original.timezone.to_owned();
+    config.occurrence_count = original.occurrence_count;
+    config.disabled = original.disabled.to_owned();
+    config.last_fired_at = original.last_fired_at;
+    // the rotation only carries on through the same messages
+    if config.messages == original.messages && config.selection == original.selection {
+        config.cursor = original.cursor;
+        config.remaining = original.remaining.to_owned();
+    }
+    let id = config.id.to_owned().unwrap_or_default();
 
-            let _ = create_schedule(u_id, config, schedule);
-        }
+    audit_job(path, &format!("user {} edited", u_id));
+    delete_job(path);
+
+    if !create_schedule(&owner, config, schedule) {
+        create_schedule(&owner, original, original_schedule);
+        return Err("I couldn't save the changes, the job is left as it was.".to_owned());
+    }
+
+    if let Ok((_, path)) = get_user_job(&owner, &id) {
+        audit_job(&path, &format!("user {} changed it to", u_id));
     }
+
+    Ok(())
 }
 
 fn get_state(u_id: &String) -> Option<ConfigInProgress> {
@@ -648,6 +2807,17 @@ fn get_state(u_id: &String) -> Option<ConfigInProgress> {
     }
 }
 
+/// Id of the wizard the user is in, if any.
+pub fn get_wizard_session(u_id: &String) -> Option<String> {
+    get_state(u_id).and_then(|state| state.session)
+}
+
+// the random end of a ulid is plenty to tell wizards apart
+fn new_session_id() -> String {
+    let id = new_job_id();
+    id[id.len() - 8..].to_owned()
+}
+
 fn save_state(u_id: &String, config_in_progress: &ConfigInProgress) {
     let file_path = format!("/mnt/data/in_progress/{}", &u_id);
 
@@ -665,6 +2835,20 @@ fn save_state(u_id: &String, config_in_progress: &ConfigInProgress) {
     }
 }
 
+/// The chat picked with /jobs in private, so later /jobs commands act on it.
+fn get_managed_chat(u_id: &String) -> Option<String> {
+    read_to_string(format!("/mnt/data/managing/{}", u_id))
+        .ok()
+        .map(|chat_id| chat_id.trim().to_owned())
+}
+
+fn set_managed_chat(u_id: &String, chat_id: &String) {
+    _ = create_dir_all("/mnt/data/managing");
+    if let Ok(mut file) = File::create(format!("/mnt/data/managing/{}", u_id)) {
+        let _ = file.write_all(chat_id.as_bytes());
+    }
+}
+
 fn delete_state(u_id: &String) {
     let file_path = format!("in_progress/{}", &u_id);
     let path = Path::new(&file_path);
@@ -674,24 +2858,131 @@ fn delete_state(u_id: &String) {
     }
 }
 
-fn get_days_by_month(month: &String) -> i32 {
-    match month.to_lowercase().as_str() {
-        "january" => 31,
-        "february" => 28,
-        "march" => 31,
-        "april" => 30,
-        "may" => 31,
-        "june" => 30,
-        "july" => 31,
-        "august" => 31,
-        "september" => 30,
-        "october" => 31,
-        "november" => 30,
-        "december" => 31,
-        _ => 0,
+fn get_days_by_month(month: &String, year: i32) -> i32 {
+    match get_last_day_of_month(year, get_month_number(&Some(month.to_owned()))) {
+        Some(last) => last.day() as i32,
+        None => 0,
+    }
+}
+
+const ORDINALS: [(i32, &str); 5] = [(1, "1st"), (2, "2nd"), (3, "3rd"), (4, "4th"), (-1, "Last")];
+
+fn get_weekday_from_int(weekday: u32) -> Weekday {
+    Weekday::try_from(weekday as u8).unwrap()
+}
+
+fn get_monthly_rule_display(rule: MonthlyRule) -> String {
+    match rule {
+        MonthlyRule::Date => "On a date".to_owned(),
+        MonthlyRule::LastDay => "Last day".to_owned(),
+        MonthlyRule::LastBusinessDay => "Last business day".to_owned(),
+        MonthlyRule::NthWeekday(nth, weekday) => {
+            let ordinal = ORDINALS.iter().find(|(n, _)| *n == nth).unwrap().1;
+            format!("{} {}", ordinal, get_weekday_from_int(weekday))
+        }
+    }
+}
+
+// as in "monthly on the ..."
+fn get_monthly_rule_description(rule: MonthlyRule) -> String {
+    match rule {
+        MonthlyRule::NthWeekday(nth, weekday) => {
+            let ordinal = ["first", "second", "third", "fourth"]
+                .get(nth as usize - 1)
+                .unwrap_or(&"last");
+            format!(
+                "{} {}",
+                ordinal,
+                string_from_weekday(get_weekday_from_int(weekday))
+            )
+        }
+        rule => get_monthly_rule_display(rule).to_lowercase(),
+    }
+}
+
+fn string_from_weekday(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+fn get_option_monthly_rules() -> Vec<Vec<String>> {
+    let mut options = vec![
+        vec![get_monthly_rule_display(MonthlyRule::Date)],
+        vec![
+            get_monthly_rule_display(MonthlyRule::LastDay),
+            get_monthly_rule_display(MonthlyRule::LastBusinessDay),
+        ],
+    ];
+
+    for (nth, _) in ORDINALS {
+        options.push(
+            (0..7)
+                .map(|weekday| get_monthly_rule_display(MonthlyRule::NthWeekday(nth, weekday)))
+                .collect(),
+        );
+    }
+
+    options
+}
+
+/// Reads a button's label, or a typed rule like "second tuesday".
+fn parse_monthly_rule(message: &str) -> Option<MonthlyRule> {
+    let message = message.trim().to_lowercase();
+
+    match message.as_str() {
+        "on a date" | "date" => return Some(MonthlyRule::Date),
+        "last day" => return Some(MonthlyRule::LastDay),
+        "last business day" | "last weekday" => return Some(MonthlyRule::LastBusinessDay),
+        _ => {}
+    }
+
+    let (nth, weekday) = message.split_once(' ')?;
+    let nth = match nth {
+        "1st" | "first" => 1,
+        "2nd" | "second" => 2,
+        "3rd" | "third" => 3,
+        "4th" | "fourth" => 4,
+        "last" => -1,
+        _ => return None,
+    };
+
+    parse_weekday(weekday)
+        .map(|weekday| MonthlyRule::NthWeekday(nth, weekday.num_days_from_monday()))
+}
+
+/// Reads "3 weeks", "every 10 days" or "2 months".
+fn parse_interval(message: &str) -> Option<(u32, IntervalUnit)> {
+    let message = message.trim().to_lowercase();
+    let message = message.strip_prefix("every ").unwrap_or(&message);
+    let (every, unit) = message.split_once(' ')?;
+
+    match (every.parse::<u32>(), IntervalUnit::parse(unit.trim())) {
+        (Ok(every), Some(unit)) if every > 0 => Some((every, unit)),
+        _ => None,
+    }
+}
+
+fn get_missing_day_display(missing_day: MissingDay) -> String {
+    match missing_day {
+        MissingDay::Skip => "Skip that month".to_owned(),
+        MissingDay::LastDay => "Send on the last day".to_owned(),
+        MissingDay::NextDay => "Send on the 1st after".to_owned(),
     }
 }
 
+fn parse_missing_day(message: &str) -> Option<MissingDay> {
+    [MissingDay::Skip, MissingDay::LastDay, MissingDay::NextDay]
+        .into_iter()
+        .find(|missing_day| get_missing_day_display(*missing_day).eq_ignore_ascii_case(message))
+}
+
 fn get_month_number(option: &Option<String>) -> u32 {
     match option {
         Some(month) => match month.to_lowercase().as_str() {
@@ -713,7 +3004,7 @@ fn get_month_number(option: &Option<String>) -> u32 {
     }
 }
 
-fn get_option_days(month: &String) -> Vec<Vec<String>> {
+fn get_option_days(month: &String, year: i32) -> Vec<Vec<String>> {
     let mut result: Vec<Vec<String>> = Vec::new();
 
     result.push(Vec::new());
@@ -725,7 +3016,7 @@ fn get_option_days(month: &String) -> Vec<Vec<String>> {
     let mut i = 1;
     let mut j = 0;
     let mut index = 0;
-    while i <= get_days_by_month(month) {
+    while i <= get_days_by_month(month, year) {
         result.get_mut(index).unwrap().push(i.to_string());
         i += 1;
         j += 1;
@@ -739,23 +3030,6 @@ fn get_option_days(month: &String) -> Vec<Vec<String>> {
     result
 }
 
-fn get_option_months() -> Vec<Vec<String>> {
-    vec![
-        vec!["January".to_owned()],
-        vec!["February".to_owned()],
-        vec!["March".to_owned()],
-        vec!["April".to_owned()],
-        vec!["May".to_owned()],
-        vec!["June".to_owned()],
-        vec!["July".to_owned()],
-        vec!["August".to_owned()],
-        vec!["September".to_owned()],
-        vec!["October".to_owned()],
-        vec!["November".to_owned()],
-        vec!["December".to_owned()],
-    ]
-}
-
 fn get_month_from_int(item: i32) -> Option<Month> {
     match item {
         1 => Some(Month::January),
@@ -775,67 +3049,122 @@ fn get_month_from_int(item: i32) -> Option<Month> {
 }
 
 fn process_list(u_id: &String) -> FlowStatus {
+    FlowStatus::Info(list_jobs(&get_user_jobs(u_id)))
+}
+
+fn list_jobs(jobs: &Vec<(String, String)>) -> String {
     let mut message = String::from("");
 
-    let user_directory = format!("users/{}", u_id);
-
-    let user_directory_path = Path::new(&user_directory);
-
-    for file in user_directory_path.read_dir().unwrap() {
-        let path = file.unwrap().path();
-        let i = path.file_name().unwrap().to_str().unwrap().to_string();
-
-        let real_path = read_link(path).unwrap().to_str().unwrap().to_string();
-        let all_directories = real_path.split("/").collect::<Vec<&str>>();
-        let data = serde_yaml::from_str::<Config>(&read_to_string(&real_path).unwrap()).unwrap();
-        let frequency = all_directories.get(5).unwrap();
-        let info = match *frequency {
-            "daily" => {
-                let time = all_directories.get(5).unwrap();
-                format!("{}: Sent daily at {} to {} \n", i, time, data.chat_id)
-            }
-            "weekly" => {
-                let weekday = all_directories.get(4).unwrap();
-                let time = all_directories.get(5).unwrap();
-                format!(
-                    "{}: Sent weekly on {} at {} to {}\n",
-                    i, weekday, time, data.chat_id
-                )
-            }
-            "biweekly" => {
-                let weekday = all_directories.get(5).unwrap();
-                let time = all_directories.get(6).unwrap();
-                format!(
-                    "{}: Sent bi-weekly on {} at {} to {}\n",
-                    i, weekday, time, data.chat_id
-                )
-            }
-            "monthly" => {
-                let day = all_directories.get(4).unwrap().parse::<i32>().unwrap();
-                let time = all_directories.get(5).unwrap();
-                format!(
-                    "{}: Sent monthly on day {} at {} to {}\n",
-                    i, day, time, data.chat_id
-                )
-            }
-            "yearly" => {
-                let day = all_directories.get(5).unwrap().parse::<i32>().unwrap();
-                let time = all_directories.get(6).unwrap();
-                let month = all_directories.get(4).unwrap();
-                let month_name =
-                    string_from_month(get_month_from_int(month.parse::<i32>().unwrap()).unwrap());
-                format!(
-                    "{}: Sent yearly on {} on day {} at {} to {}\n",
-                    i, month_name, day, time, data.chat_id
-                )
-            }
-            _ => "".to_owned(),
+    for (i, real_path) in jobs {
+        let data = match ensure_id(real_path) {
+            Ok(data) => data,
+            Err(_) => continue,
         };
 
-        message.push_str(&info);
+        message.push_str(&describe_job(
+            &format!("{} [{}]", i, data.short_id()),
+            real_path,
+            &data,
+        ));
+
+        if let Some(next) = get_next_display(real_path, &data) {
+            message.push_str(&format!("   Next: {}\n", next));
+        }
+        if let Some(limits) = data.get_limits_display() {
+            message.push_str(&format!("   Limits: {}\n", limits));
+        }
+        if let Some(skips) = data.get_skips_display() {
+            message.push_str(&format!("   Skipping: {}\n", skips));
+        }
+        if let Some(catch_up) = data.get_catch_up_display() {
+            message.push_str(&format!("   Missed deliveries: {}\n", catch_up));
+        }
+        if let Some(reason) = &data.disabled {
+            message.push_str(&format!("   Disabled: {}\n", reason));
+        }
+    }
+
+    message
+}
+
+// e.g. "Tue 14 Oct 09:00", in the job's timezone
+fn get_next_display(real_path: &str, data: &Config) -> Option<String> {
+    if data.disabled.is_some() {
+        return None;
+    }
+
+    let schedule = emSchedule::from_file_location(real_path)?;
+    let next = data.next_occurrences(&schedule, &Utc::now(), 1);
+
+    next.first()
+        .map(|occurrence| occurrence.format("%a %-d %b %H:%M").to_string())
+}
+
+fn describe_job(i: &str, real_path: &str, data: &Config) -> String {
+    let to = get_targets_display(&data.targets);
+
+    match emSchedule::from_file_location(real_path) {
+        Some(schedule) => format!("{}: Sent {} to {}\n", i, describe_schedule(&schedule), to),
+        None => match real_path.split("/").collect::<Vec<&str>>()[..] {
+            // biweekly/{odd}/{weekday}/{time}/{id}, until migrated to intervals
+            [.., "biweekly", _, weekday, time, _] => format!(
+                "{}: Sent bi-weekly on {} {} to {}\n",
+                i,
+                weekday,
+                get_times_display(time),
+                to
+            ),
+            _ => format!("{}: Sent to {}\n", i, to),
+        },
+    }
+}
+
+fn describe_schedule(schedule: &emSchedule) -> String {
+    let times = get_times_display(schedule.get_time());
+
+    match schedule {
+        emSchedule::Daily { .. } => format!("daily {}", times),
+        emSchedule::Weekly { weekday, .. } => {
+            format!("weekly on {} {}", string_from_weekday(*weekday), times)
+        }
+        emSchedule::Interval {
+            every,
+            unit,
+            anchor,
+            ..
+        } => format!("every {} {} from {} {}", every, unit, anchor, times),
+        emSchedule::Monthly { day, .. } => format!("monthly on day {} {}", day, times),
+        emSchedule::MonthlyLastDay { .. } => format!("monthly on the last day {}", times),
+        emSchedule::MonthlyNthWeekday { nth, weekday, .. } => format!(
+            "monthly on the {} {}",
+            get_monthly_rule_description(MonthlyRule::NthWeekday(
+                *nth,
+                weekday.num_days_from_monday()
+            )),
+            times
+        ),
+        emSchedule::MonthlyLastBusinessDay { .. } => {
+            format!("monthly on the last business day {}", times)
+        }
+        emSchedule::Yearly { month, day, .. } => {
+            format!("yearly on {} {} {}", string_from_month(*month), day, times)
+        }
+    }
+}
+
+fn get_targets_display(targets: &[Target]) -> String {
+    match targets {
+        [target] => target.to_string(),
+        _ => format!("{} chats", targets.len()),
     }
+}
 
-    FlowStatus::Info(message)
+fn get_selection_display(selection: Selection) -> String {
+    match selection {
+        Selection::RoundRobin => String::from("in order"),
+        Selection::Random => String::from("at random"),
+        Selection::Shuffle => String::from("at random without repeats"),
+    }
 }
 
 fn string_from_month(month: Month) -> String {
@@ -857,19 +3186,40 @@ fn string_from_month(month: Month) -> String {
     item.to_string()
 }
 
+/// A job is referred to by its number in /list or by its id.
+fn is_job_reference(reference: &str) -> bool {
+    let reference = reference.trim_start_matches('#');
+
+    !reference.is_empty() && reference.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn get_jobs_usage() -> String {
+    "Use /jobs to list a chat's jobs, /jobs view|edit|delete|pause|resume <number or id> to manage one, or /jobs audit to see recent changes.".to_owned()
+}
+
 fn get_error(desired_value: &DesiredValue) -> Result<UserInput, String> {
     match desired_value {
-        DesiredValue::Chat => Err(
-            "Please provide a chat or channel by mentioning it (using @ before the name.)"
+        DesiredValue::Chat | DesiredValue::ManagedChat | DesiredValue::ImportChat => Err(
+            "Please pick the chat with the button below, forward me a post from it, mention it (using @ before the name), or send its numeric id or t.me link."
+                .to_owned(),
+        ),
+        DesiredValue::Frequency => Err(
+            "Please use the provided buttons to select a frequency, or describe it, e.g. \"every other friday at 18:30\"."
                 .to_owned(),
         ),
-        DesiredValue::Frequency => {
-            Err("Please use the provided buttons to select a frequency.".to_owned())
+        DesiredValue::AnotherChat => {
+            Err("Please use the provided buttons to select Yes/No.".to_owned())
         }
         DesiredValue::HasToken => {
             Err("Please use the provided buttons to select Yes/No.".to_owned())
         }
         DesiredValue::Message => Err("Please provide a message to send.".to_owned()),
+        DesiredValue::AnotherMessage => {
+            Err("Please use the provided buttons to select Yes/No.".to_owned())
+        }
+        DesiredValue::Selection => {
+            Err("Please use the provided buttons to choose how messages are picked.".to_owned())
+        }
         DesiredValue::None => Err("".to_owned()),
         DesiredValue::StartDay => Err("Please use the desired buttons to select a day.".to_owned()),
         DesiredValue::StartMonth => {
@@ -878,11 +3228,829 @@ fn get_error(desired_value: &DesiredValue) -> Result<UserInput, String> {
         DesiredValue::StartTime => {
             Err("Please provide a 24-hour time in the hour:minute format.".to_owned())
         }
-        DesiredValue::Token => Err("Please provide the bot token.".to_owned()),
+        DesiredValue::Token => Err(
+            "That doesn't look like a bot token. BotFather hands them out like 123456:ABC-DEF1234ghIkl."
+                .to_owned(),
+        ),
+        DesiredValue::Interval => Err(
+            "Please send a number and a unit, e.g. \"3 weeks\", \"10 days\" or \"2 months\"."
+                .to_owned(),
+        ),
+        DesiredValue::MonthlyRule => {
+            Err("Please use the provided buttons to pick the day of the month.".to_owned())
+        }
+        DesiredValue::MissingDay => {
+            Err("Please use the provided buttons to pick what happens in shorter months.".to_owned())
+        }
+        DesiredValue::ConfirmSchedule => {
+            Err("Please use the provided buttons to select Yes/No.".to_owned())
+        }
+        DesiredValue::Limits => {
+            Err("Please use the provided buttons to pick a limit, or Done.".to_owned())
+        }
+        DesiredValue::StartDate | DesiredValue::EndDate => Err(
+            "Please pick a date from the calendar, or send \"none\".".to_owned(),
+        ),
+        DesiredValue::MaxDeliveries => {
+            Err("Please send a number greater than zero, or \"none\".".to_owned())
+        }
+        DesiredValue::GraceMinutes => {
+            Err("Please send a number of minutes, or \"none\".".to_owned())
+        }
+        DesiredValue::SkipDates => Err(
+            "Please send dates like 2026-12-24, an .ics calendar file, or \"none\".".to_owned(),
+        ),
+        DesiredValue::Confirm => {
+            Err("Please use the provided buttons to confirm, edit or cancel.".to_owned())
+        }
+        DesiredValue::Import => {
+            Err("Please send a backup made with /export or an .ics calendar file.".to_owned())
+        }
+        DesiredValue::EditField => {
+            Err("Please use the provided buttons to pick what to change.".to_owned())
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn new_state() -> ConfigInProgress {
+        ConfigInProgress::new(DesiredValue::Message)
+    }
+
+    // every chat can be posted to
+    struct OpenChats;
+
+    impl ChatDirectory for OpenChats {
+        fn verify_chat(&self, target: Target, _: &String) -> Result<Target, String> {
+            Ok(target)
+        }
+
+        fn is_chat_admin(&self, _: &String, _: &String) -> bool {
+            true
+        }
+
+        fn read_document(&self, _: &String) -> Option<String> {
+            None
+        }
+
+        fn get_bot_name(&self, _: Option<&String>) -> Option<String> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_another_message_loops_back() {
+        let mut state = new_state();
+        process_desired_message(&mut state, UserInput::Message("one".to_owned()));
+        state.move_to_next_step();
+        process_another_message(&mut state, UserInput::YesNo(true));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Message));
+
+        process_desired_message(&mut state, UserInput::Message("two".to_owned()));
+        state.move_to_next_step();
+        process_another_message(&mut state, UserInput::YesNo(false));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Selection));
+    }
+
+    #[test]
+    fn test_parse_chat_link() {
+        assert_eq!(
+            parse_chat_link("https://t.me/c/1234567/42"),
+            Some(Target::new("-1001234567".to_owned()))
+        );
+        assert_eq!(
+            parse_chat_link("t.me/c/1234567/42/100"),
+            Some(Target::in_thread("-1001234567".to_owned(), 42))
+        );
+        assert_eq!(
+            parse_chat_link("https://t.me/c/1234567/100?thread=42"),
+            Some(Target::in_thread("-1001234567".to_owned(), 42))
+        );
+        assert_eq!(
+            parse_chat_link("https://t.me/mygroup/3/17"),
+            Some(Target::in_thread("@mygroup".to_owned(), 3))
+        );
+        assert_eq!(
+            parse_chat_link("https://t.me/mychannel/123"),
+            Some(Target::new("@mychannel".to_owned()))
+        );
+        assert_eq!(
+            parse_chat_link("https://t.me/mychannel"),
+            Some(Target::new("@mychannel".to_owned()))
+        );
+        assert_eq!(parse_chat_link("https://t.me/+AbCdEf"), None);
+        assert_eq!(parse_chat_link("hello"), None);
+    }
+
+    #[test]
+    fn test_single_message_skips_selection() {
+        let mut state = new_state();
+        process_desired_message(&mut state, UserInput::Message("one".to_owned()));
+        state.move_to_next_step();
+        process_another_message(&mut state, UserInput::YesNo(false));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Frequency));
+    }
+
+    #[test]
+    fn test_parse_jobs_command() {
+        assert!(matches!(
+            load_command(&"/jobs@scheduler_bot".to_owned()),
+            Ok(UserInput::Command(Command::Jobs(JobsAction::List)))
+        ));
+        assert!(matches!(
+            load_command(&"/jobs pause 2".to_owned()),
+            Ok(UserInput::Command(Command::Jobs(JobsAction::Pause(number)))) if number == "2"
+        ));
+        assert!(matches!(
+            load_command(&"/jobs audit".to_owned()),
+            Ok(UserInput::Command(Command::Jobs(JobsAction::Audit)))
+        ));
+        assert!(load_command(&"/jobs delete".to_owned()).is_err());
+        assert!(load_command(&"/jobs delete 2!".to_owned()).is_err());
+        assert!(load_command(&"/jobs frobnicate 2".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_edit_chats_returns_to_confirm() {
+        let mut state = new_state();
+        state.desired_value = DesiredValue::Confirm;
+        assert_eq!(
+            process_confirm(&mut state, UserInput::YesNo(false), &"1".to_owned()),
+            Ok(false)
+        );
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::EditField));
+
+        process_edit_field(&mut state, UserInput::Field(Field::Chats));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Chat));
+
+        process_chat(&mut state, UserInput::Chat(Target::new("@one".to_owned())));
+        state.move_to_next_step();
+        process_another_chat(&mut state, UserInput::YesNo(true));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Chat));
+
+        process_chat(&mut state, UserInput::Chat(Target::new("@two".to_owned())));
+        state.move_to_next_step();
+        process_another_chat(&mut state, UserInput::YesNo(false));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Confirm));
+        assert_eq!(state.chats.len(), 2);
+        assert!(state.editing.is_none());
+    }
+
+    #[test]
+    fn test_next_occurrences() {
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Weekly);
+        state.first_execution_month = Some("October".to_owned());
+        state.first_execution_day = Some("1".to_owned());
+        state.first_execution_time = Some("09:00".to_owned());
+
+        // without a year, october 1st would be next year's once it has passed
+        let year = Utc::now().year();
+        state.first_execution_year = Some(year);
+        let now = NaiveDate::from_ymd_opt(year, 10, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let occurrences = get_next_occurrences(&state, &now, 3);
+
+        let expected = [15, 22, 29]
+            .iter()
+            .map(|day| {
+                NaiveDate::from_ymd_opt(year, 10, *day)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap()
+            })
+            .collect::<Vec<NaiveDateTime>>();
+        assert_eq!(occurrences, expected);
+    }
+
+    #[test]
+    fn test_back_follows_branches() {
+        let mut state = new_state();
+        assert!(!state.move_to_previous_step());
+
+        process_desired_message(&mut state, UserInput::Message("one".to_owned()));
+        state.move_to_next_step();
+        process_another_message(&mut state, UserInput::YesNo(false));
+        state.move_to_next_step();
+        process_frequency(&mut state, UserInput::Frequency(Schedule::Daily));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::StartTime));
+
+        // daily jobs skip the date steps in both directions
+        assert!(state.move_to_previous_step());
+        assert!(matches!(state.desired_value, DesiredValue::Frequency));
+        assert!(state.get_message().message.ends_with("Currently: Daily"));
+
+        assert!(state.move_to_previous_step());
+        assert!(matches!(state.desired_value, DesiredValue::AnotherMessage));
+        assert!(state.move_to_previous_step());
+        assert!(matches!(state.desired_value, DesiredValue::Message));
+        assert!(state.get_message().message.ends_with("Currently: one"));
+
+        // the message is kept until another one replaces it
+        process_desired_message(&mut state, UserInput::Message("uno".to_owned()));
+        assert_eq!(state.messages, [Message::Message("uno".to_owned())]);
+
+        let mut state = Some(state);
+        state.as_mut().unwrap().desired_value = DesiredValue::Token;
+        assert!(load_text_input(&mut state, &"€abc".to_owned()).is_err());
+        let input = load_text_input(&mut state, &" 123456:ABC-def_1 ".to_owned()).unwrap();
+        let state = state.as_mut().unwrap();
+        process_token(state, input);
+        assert!(state.get_message().message.ends_with("Currently: ...ef_1"));
+
+        // the summary names the bot, by its id until its name is known
+        state.has_token = Some(true);
+        state.first_execution_time = Some("09:00".to_owned());
+        assert!(state.get_summary().ends_with("Sent by: bot 123456"));
+        state.bot_name = Some("@standup_bot".to_owned());
+        assert!(state.get_summary().ends_with("Sent by: @standup_bot"));
+    }
+
+    #[test]
+    fn test_calendar_date_skips_day() {
+        let mut state = Some(new_state());
+        state.as_mut().unwrap().schedule = Some(Schedule::Monthly);
+        state.as_mut().unwrap().desired_value = DesiredValue::StartMonth;
+
+        let input = load_text_input(&mut state, &"2027-03-05".to_owned()).unwrap();
+        let state = state.as_mut().unwrap();
+        process_month(state, input);
+        state.move_to_next_step();
+
+        assert!(matches!(state.desired_value, DesiredValue::StartTime));
+        assert_eq!(
+            get_first_execution_date(state),
+            NaiveDate::from_ymd_opt(2027, 3, 5).unwrap()
+        );
+        assert!(matches!(
+            state.get_previous_step(),
+            Some(DesiredValue::StartMonth)
+        ));
+    }
+
+    #[test]
+    fn test_typed_schedule_asks_for_what_is_missing() {
+        let mut state = Some(new_state());
+        state.as_mut().unwrap().desired_value = DesiredValue::Frequency;
+
+        // no time given, so that's asked for after confirming
+        let input = load_text_input(&mut state, &"every other friday".to_owned()).unwrap();
+        let state = state.as_mut().unwrap();
+        apply_typed_schedule(
+            state,
+            match input {
+                UserInput::Schedule(parsed) => parsed,
+                _ => panic!("expected a typed schedule"),
+            },
+        );
+
+        assert!(matches!(state.desired_value, DesiredValue::ConfirmSchedule));
+        assert!(matches!(state.schedule, Some(Schedule::Biweekly)));
+        assert!(state
+            .get_message()
+            .message
+            .contains("every other Friday, first on Fri"));
+
+        process_confirm_schedule(state, UserInput::YesNo(true));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::StartTime));
+    }
+
+    #[test]
+    fn test_typed_schedule_at_any_step() {
+        let typed = |state: &mut Option<ConfigInProgress>, text: &str| match load_text_input(
+            state,
+            &text.to_owned(),
+        )
+        .unwrap()
+        {
+            UserInput::Schedule(parsed) => apply_typed_schedule(state.as_mut().unwrap(), parsed),
+            _ => panic!("expected a typed schedule"),
+        };
+
+        // a date and time before the frequency is kept while that's asked for
+        let mut state = Some(new_state());
+        state.as_mut().unwrap().desired_value = DesiredValue::Selection;
+        typed(&mut state, "tomorrow 18:30");
+        {
+            let state = state.as_mut().unwrap();
+            assert!(matches!(state.desired_value, DesiredValue::Frequency));
+            assert!(state.get_message().message.contains("at 18:30"));
+
+            process_frequency(state, UserInput::Frequency(Schedule::Weekly));
+            state.move_to_next_step();
+            assert!(matches!(state.desired_value, DesiredValue::ConfirmSchedule));
+            assert!(state.get_message().message.contains("at 18:30, first on"));
+
+            process_confirm_schedule(state, UserInput::YesNo(true));
+            state.move_to_next_step();
+            assert!(matches!(state.desired_value, DesiredValue::Limits));
+            assert!(!state.typed_date);
+        }
+
+        // and at the steps after the schedule, e.g. while picking limits
+        typed(&mut state, "every monday at 9am");
+        {
+            let state = state.as_mut().unwrap();
+            assert!(matches!(state.desired_value, DesiredValue::ConfirmSchedule));
+            assert!(matches!(state.schedule, Some(Schedule::Weekly)));
+            assert_eq!(state.first_execution_time, Some("09:00".to_owned()));
+        }
+
+        // from the summary it goes back to the summary
+        state.as_mut().unwrap().desired_value = DesiredValue::Confirm;
+        typed(&mut state, "every other friday");
+        assert!(matches!(
+            state.as_ref().unwrap().editing,
+            Some(Field::Schedule)
+        ));
+    }
+
+    #[test]
+    fn test_missing_day_is_asked_for_late_days() {
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Monthly);
+        state.first_execution_year = Some(2027);
+        state.first_execution_month = Some("January".to_owned());
+        state.first_execution_day = Some("31".to_owned());
+        state.first_execution_time = Some("09:00".to_owned());
+        state.desired_value = DesiredValue::StartMonth;
+
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::MissingDay));
+        assert!(matches!(
+            state.get_previous_step(),
+            Some(DesiredValue::StartMonth)
+        ));
+
+        state.missing_day = parse_missing_day("send on the last day");
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::StartTime));
+
+        let now = NaiveDate::from_ymd_opt(2027, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let days = get_next_occurrences(&state, &now, 4)
+            .iter()
+            .map(|occurrence| occurrence.date().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            days,
+            ["2027-01-31", "2027-02-28", "2027-03-31", "2027-04-30"]
+        );
+
+        assert_eq!(get_days_by_month(&"February".to_owned(), 2028), 29);
+        assert_eq!(get_days_by_month(&"February".to_owned(), 2027), 28);
+    }
+
+    #[test]
+    fn test_monthly_rule_skips_date() {
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Monthly);
+        state.desired_value = DesiredValue::Frequency;
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::MonthlyRule));
+
+        state.monthly_rule = parse_monthly_rule("Second Tuesday");
+        assert_eq!(state.monthly_rule, Some(MonthlyRule::NthWeekday(2, 1)));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::StartTime));
+        assert!(matches!(
+            state.get_previous_step(),
+            Some(DesiredValue::MonthlyRule)
+        ));
+
+        state.first_execution_time = Some("09:00".to_owned());
+        assert_eq!(
+            state.get_schedule_display(),
+            "monthly on the second Tuesday at 09:00"
+        );
+
+        let now = NaiveDate::from_ymd_opt(2026, 10, 14)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let days = get_next_occurrences(&state, &now, 2)
+            .iter()
+            .map(|occurrence| occurrence.date().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(days, ["2026-11-10", "2026-12-08"]);
+
+        assert_eq!(
+            parse_monthly_rule("Last business day"),
+            Some(MonthlyRule::LastBusinessDay)
+        );
+        assert_eq!(parse_monthly_rule("fifth monday"), None);
+    }
+
+    #[test]
+    fn test_interval_schedule() {
+        assert_eq!(
+            parse_interval("every 3 weeks"),
+            Some((3, IntervalUnit::Weeks))
+        );
+        assert_eq!(parse_interval("10 Days"), Some((10, IntervalUnit::Days)));
+        assert_eq!(parse_interval("0 months"), None);
+
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Interval(3, IntervalUnit::Weeks));
+        state.desired_value = DesiredValue::Frequency;
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Interval));
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::StartMonth));
+        assert!(matches!(
+            state.get_previous_step(),
+            Some(DesiredValue::Interval)
+        ));
+
+        state.first_execution_year = Some(2026);
+        state.first_execution_month = Some("December".to_owned());
+        state.first_execution_day = Some("29".to_owned());
+        state.first_execution_time = Some("09:00".to_owned());
+
+        let now = NaiveDate::from_ymd_opt(2026, 12, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let days = get_next_occurrences(&state, &now, 3)
+            .iter()
+            .map(|occurrence| occurrence.date().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(days, ["2026-12-29", "2027-01-19", "2027-02-09"]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Daily);
+        state.first_execution_time = Some("09:00".to_owned());
+        state.desired_value = DesiredValue::StartTime;
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Limits));
+
+        let mut state = Some(state);
+        load_text_input(&mut state, &"End date".to_owned()).unwrap();
+        let state = state.as_mut().unwrap();
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::EndDate));
+
+        state.start_date = NaiveDate::from_ymd_opt(2100, 1, 10);
+        assert!(load_limit_date(state, "2100-01-05").is_err());
+        assert!(load_limit_date(state, "2100-01-20").is_ok());
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Limits));
+
+        state.max_deliveries = Some(3);
+        assert_eq!(
+            state.get_limits_display(),
+            Some("from 2100-01-10, until 2100-01-20, at most 3 times".to_owned())
+        );
+
+        let now = NaiveDate::from_ymd_opt(2099, 12, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let days = get_next_occurrences(state, &now, 5)
+            .iter()
+            .map(|occurrence| occurrence.date().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(days, ["2100-01-10", "2100-01-11", "2100-01-12"]);
+
+        state.limit = None;
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::Chat));
+        assert!(matches!(
+            state.get_previous_step(),
+            Some(DesiredValue::Limits)
+        ));
+    }
+
+    #[test]
+    fn test_skip_dates() {
+        let mut state = Some(new_state());
+        state.as_mut().unwrap().desired_value = DesiredValue::Limits;
+        load_text_input(&mut state, &"Skip missed".to_owned()).unwrap();
+        state.as_mut().unwrap().move_to_next_step();
+        load_text_input(&mut state, &"Grace period".to_owned()).unwrap();
+        state.as_mut().unwrap().move_to_next_step();
+        assert!(load_text_input(&mut state, &"soon".to_owned()).is_err());
+        load_text_input(&mut state, &"45".to_owned()).unwrap();
+        state.as_mut().unwrap().move_to_next_step();
+        load_text_input(&mut state, &"Skip weekends".to_owned()).unwrap();
+        let state = state.as_mut().unwrap();
+        state.move_to_next_step();
+        assert!(state.skip_weekends);
+        assert_eq!(state.catch_up, CatchUp::Skip);
+        assert_eq!(state.to_config().grace_minutes, Some(45));
+        assert!(matches!(state.desired_value, DesiredValue::Limits));
+
+        assert!(load_skip_dates(state, "2100-01-04, tomorrow").is_err());
+        load_skip_dates(state, "2100-01-05 2000-01-01").unwrap();
+        load_skip_dates(
+            state,
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;VALUE=DATE:21000106\nEND:VEVENT\nEND:VCALENDAR",
+        )
+        .unwrap();
+        assert_eq!(
+            state.skip_dates,
+            [
+                NaiveDate::from_ymd_opt(2100, 1, 5).unwrap(),
+                NaiveDate::from_ymd_opt(2100, 1, 6).unwrap()
+            ]
+        );
+
+        // 2100-01-01 is a friday
+        state.schedule = Some(Schedule::Daily);
+        state.first_execution_time = Some("09:00".to_owned());
+        let now = NaiveDate::from_ymd_opt(2100, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let days = get_next_occurrences(state, &now, 3)
+            .iter()
+            .map(|occurrence| occurrence.date().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(days, ["2100-01-01", "2100-01-04", "2100-01-07"]);
+    }
+
+    #[test]
+    fn test_several_times() {
+        let mut state = Some(new_state());
+        {
+            let state = state.as_mut().unwrap();
+            state.schedule = Some(Schedule::Daily);
+            state.desired_value = DesiredValue::StartTime;
+        }
+
+        // the second 08:00 takes it away again
+        for answer in ["08:00", "18:00", "08:00", "12:00", "Done"] {
+            let input = load_text_input(&mut state, &answer.to_owned()).unwrap();
+            let state = state.as_mut().unwrap();
+            process_time(state, input);
+            state.move_to_next_step();
+        }
+
+        let state = state.as_mut().unwrap();
+        assert!(matches!(state.desired_value, DesiredValue::Limits));
+        assert_eq!(state.first_execution_time, Some("12:00,18:00".to_owned()));
+        assert_eq!(state.get_schedule_display(), "daily at 12:00 and 18:00");
+
+        let now = NaiveDate::from_ymd_opt(2026, 10, 14)
+            .unwrap()
+            .and_hms_opt(13, 0, 0)
+            .unwrap();
+        let times = get_next_occurrences(state, &now, 3)
+            .iter()
+            .map(|occurrence| occurrence.format("%d %H:%M").to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(times, ["14 18:00", "15 12:00", "15 18:00"]);
+
+        assert_eq!(
+            describe_schedule(&emSchedule::Weekly {
+                weekday: Weekday::Mon,
+                time: "every-2h-08:00-18:00".to_owned(),
+            }),
+            "weekly on Monday every 2 hours from 08:00 to 18:00"
+        );
+    }
+
+    #[test]
+    fn test_confirm_fails_without_a_schedule() {
+        let mut state = new_state();
+        state.schedule = Some(Schedule::Daily);
+        state.desired_value = DesiredValue::Confirm;
+
+        // no time was picked, so nothing can be saved
+        assert!(process_confirm(&mut state, UserInput::YesNo(true), &"42".to_owned()).is_err());
+        assert!(matches!(state.desired_value, DesiredValue::Confirm));
+        assert_eq!(
+            process_confirm(&mut state, UserInput::YesNo(false), &"42".to_owned()),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_restore_with_another_users_id() {
+        let root = tempfile::tempdir().unwrap();
+        event_manager::set_data_root(&root.path().to_string_lossy());
+
+        let new_job = |time: &str| {
+            let mut config = Config::new(
+                vec![Target::new("-100123".to_owned())],
+                vec![Message::Message("hi".to_owned())],
+                Selection::RoundRobin,
+            );
+            config.id = Some("01jobofsomeoneelse".to_owned());
+            let schedule = emSchedule::Daily {
+                time: time.to_owned(),
+            };
+            (config, schedule)
+        };
+
+        let (config, schedule) = new_job("09:00");
+        assert!(create_schedule(&"7".to_owned(), config, schedule));
+
+        // in another slot, so nothing but the id clashes
+        let backup = export_bundle(vec![new_job("10:00")], false).unwrap();
+        match restore_bundle(&"42".to_owned(), &backup, &OpenChats) {
+            FlowStatus::DoneWithMessage(message) => {
+                assert!(message.starts_with("Restored 1 of 1 job(s)."))
+            }
+            _ => panic!("expected a report"),
+        }
+
+        let (_, path) = get_user_job(&"42".to_owned(), "1").unwrap();
+        let restored = load_config(&path).unwrap();
+        assert!(restored.id.is_some_and(|id| id != "01jobofsomeoneelse"));
+        assert_eq!(get_chat_jobs(&"-100123".to_owned()).len(), 2);
+
+        // restoring it again clashes with the user's own job only
+        match restore_bundle(&"7".to_owned(), &backup, &OpenChats) {
+            FlowStatus::DoneWithMessage(message) => {
+                assert!(message.ends_with("not restored, a job with its id already exists"))
+            }
+            _ => panic!("expected a report"),
+        }
+    }
+
+    #[test]
+    fn test_import_reports_jobs_not_saved() {
+        let root = tempfile::tempdir().unwrap();
+        event_manager::set_data_root(&root.path().to_string_lossy());
+        // the chat's index can't be written
+        File::create(root.path().join("chats")).unwrap();
+
+        let mut state = ConfigInProgress::new(DesiredValue::ImportChat);
+        state.import = Some(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Standup\nDTSTART:20261012T090000Z\n\
+            RRULE:FREQ=WEEKLY\nEND:VEVENT\nEND:VCALENDAR\n"
+                .to_owned(),
+        );
+        let import = |state: &ConfigInProgress| match process_import_chat(
+            &"42".to_owned(),
+            state,
+            UserInput::Chat(Target::new("-100123".to_owned())),
+        ) {
+            FlowStatus::DoneWithMessage(message) => message,
+            _ => panic!("expected a report"),
+        };
+
+        let message = import(&state);
+        assert!(message.starts_with("Imported 0 job(s)"));
+        assert!(message.ends_with("Not imported:\nweekly on Monday at 09:00 - could not be saved"));
+
+        remove_file(root.path().join("chats")).unwrap();
+        assert!(import(&state).starts_with("Imported 1 job(s)"));
+    }
+
+    #[test]
+    fn test_edit_job_keeps_its_schedule() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let time = || "09:00,18:00".to_owned();
+        let config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("hi".to_owned())],
+            Selection::Random,
+        );
+
+        for schedule in [
+            emSchedule::Daily { time: time() },
+            emSchedule::Weekly {
+                weekday: Weekday::Fri,
+                time: time(),
+            },
+            emSchedule::Interval {
+                every: 2,
+                unit: IntervalUnit::Weeks,
+                anchor: NaiveDate::from_ymd_opt(2026, 9, 7).unwrap(),
+                time: time(),
+            },
+            emSchedule::Interval {
+                every: 10,
+                unit: IntervalUnit::Days,
+                anchor: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+                time: time(),
+            },
+            emSchedule::Monthly {
+                day: 31,
+                time: time(),
+            },
+            emSchedule::Yearly {
+                day: 29,
+                time: time(),
+                month: Month::February,
+            },
+            emSchedule::MonthlyLastBusinessDay { time: time() },
+            emSchedule::MonthlyNthWeekday {
+                nth: -1,
+                weekday: Weekday::Tue,
+                time: time(),
+            },
+        ] {
+            let state = ConfigInProgress::from_job("job", &config, &schedule, today);
+            assert!(matches!(state.desired_value, DesiredValue::EditField));
+            assert_eq!(state.to_schedule(), Some(schedule));
+            assert_eq!(state.to_config().selection, Selection::Random);
+        }
+    }
+
+    #[test]
+    fn test_save_edited_job() {
+        let root = tempfile::tempdir().unwrap();
+        event_manager::set_data_root(&root.path().to_string_lossy());
+
+        let mut config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.occurrence_count = 4;
+        let schedule = emSchedule::Daily {
+            time: "09:00".to_owned(),
+        };
+        assert!(create_schedule(
+            &"7".to_owned(),
+            config,
+            schedule.to_owned()
+        ));
+        let (_, path) = get_chat_job(&"-100123".to_owned(), "1").unwrap();
+        let id = load_config(&path).unwrap().id.unwrap();
+
+        // another admin of the chat moves it to the evening
+        let today = Utc::now().date_naive();
+        let mut state =
+            ConfigInProgress::from_job(&path, &load_config(&path).unwrap(), &schedule, today);
+        state.first_execution_time = Some("18:00".to_owned());
+        save_edited_job(
+            &"42".to_owned(),
+            &path,
+            state.to_config(),
+            state.to_schedule().unwrap(),
+        )
+        .unwrap();
+
+        let (_, edited) = get_user_job(&"7".to_owned(), &id).unwrap();
+        assert!(edited.ends_with(&format!("18:00/{}", id)));
+        let edited = load_config(&edited).unwrap();
+        assert_eq!(edited.owner, Some("7".to_owned()));
+        assert_eq!(edited.occurrence_count, 4);
+        assert_eq!(get_chat_jobs(&"-100123".to_owned()).len(), 1);
+
+        let audit = read_audit(&"-100123".to_owned(), 2);
+        assert!(audit[0].contains("user 42 edited job: Sent daily at 09:00"));
+        assert!(audit[1].contains("user 42 changed it to job: Sent daily at 18:00"));
+
+        assert!(matches!(
+            load_command(&"/jobs edit 1".to_owned()),
+            Ok(UserInput::Command(Command::Jobs(JobsAction::Edit(number)))) if number == "1"
+        ));
+    }
+
+    #[test]
+    fn test_import() {
+        assert!(matches!(
+            load_command(&"/export ics".to_owned()),
+            Ok(UserInput::Command(Command::Export(ExportFormat::Ics)))
+        ));
+        assert!(matches!(
+            load_command(&"/export".to_owned()),
+            Ok(UserInput::Command(Command::Export(ExportFormat::Yaml)))
+        ));
+        assert!(load_command(&"/export pdf".to_owned()).is_err());
+
+        let mut state = ConfigInProgress::new(DesiredValue::Import);
+        let calendar = "BEGIN:VCALENDAR\n\
+            BEGIN:VEVENT\n\
+            SUMMARY:Check the logs\n\
+            DTSTART:20261012T080000Z\n\
+            RRULE:FREQ=MINUTELY\n\
+            END:VEVENT\n\
+            END:VCALENDAR\n";
+        assert_eq!(
+            load_import(&mut state, &calendar.to_owned()),
+            Err("Nothing in that calendar could be imported:\nline 5: RRULE:FREQ=MINUTELY - minutely repeats aren't supported".to_owned())
+        );
+
+        assert!(load_import(&mut state, &"hello".to_owned()).is_err());
+        load_import(&mut state, &calendar.replace("MINUTELY", "WEEKLY")).unwrap();
+        assert!(state.import.is_some());
+        state.move_to_next_step();
+        assert!(matches!(state.desired_value, DesiredValue::ImportChat));
+        assert!(state.get_previous_step().is_none());
+    }
 }
@@ -0,0 +1,452 @@
+use crate::Schedule;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use event_manager::format_time_range;
+use event_manager::format_times;
+use event_manager::IntervalUnit;
+
+/// A schedule typed as text, e.g. "every monday at 9am". Parts the text
+/// doesn't mention are left to the regular wizard steps.
+#[derive(Debug, PartialEq)]
+pub struct ParsedSchedule {
+    pub schedule: Option<Schedule>,
+    pub date: Option<NaiveDate>,
+    pub time: Option<NaiveTime>,
+}
+
+// words that may appear around the parts we understand
+const FILLERS: [&str; 9] = [
+    "at", "on", "the", "of", "starting", "from", "in", "and", "each",
+];
+
+/// Reads phrases like "every monday at 9am", "tomorrow 18:30",
+/// "on the 1st of every month at noon", "every other friday" or "every 3 weeks".
+/// Returns None unless every word was understood.
+pub fn parse_schedule(text: &str, now: &NaiveDateTime) -> Option<ParsedSchedule> {
+    let cleaned = text.to_lowercase().replace([',', '.', '!'], " ");
+    let words = cleaned.split_whitespace().collect::<Vec<&str>>();
+    if words.is_empty() {
+        return None;
+    }
+
+    let today = now.date();
+    let mut schedule = None;
+    let mut weekday = None;
+    let mut month_day = None;
+    let mut month = None;
+    let mut date = None;
+    let mut time = None;
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let next = words.get(i + 1).copied();
+        let after = words.get(i + 2).copied();
+
+        // (what was understood, how many words it took)
+        let used = match (word, next) {
+            ("daily", _) => {
+                schedule = Some(Schedule::Daily);
+                1
+            }
+            ("weekly", _) => {
+                schedule = Some(Schedule::Weekly);
+                1
+            }
+            ("biweekly", _) | ("fortnightly", _) => {
+                schedule = Some(Schedule::Biweekly);
+                1
+            }
+            ("monthly", _) => {
+                schedule = Some(Schedule::Monthly);
+                1
+            }
+            ("yearly", _) | ("annually", _) => {
+                schedule = Some(Schedule::Yearly);
+                1
+            }
+            ("every", Some("other")) | ("every", Some("second")) => {
+                match after.and_then(parse_weekday) {
+                    Some(day) => {
+                        weekday = Some(day);
+                        schedule = Some(Schedule::Biweekly);
+                        3
+                    }
+                    None if after == Some("week") => {
+                        schedule = Some(Schedule::Biweekly);
+                        3
+                    }
+                    None => return None,
+                }
+            }
+            ("every", Some(number)) if number.parse::<u32>().is_ok_and(|every| every > 0) => {
+                let unit = after.and_then(IntervalUnit::parse)?;
+                schedule = Some(Schedule::Interval(number.parse().unwrap(), unit));
+                3
+            }
+            ("every", Some(unit)) => {
+                if let Some(day) = parse_weekday(unit) {
+                    weekday = Some(day);
+                    schedule = Some(Schedule::Weekly);
+                } else {
+                    schedule = Some(match unit {
+                        "day" => Schedule::Daily,
+                        "week" => Schedule::Weekly,
+                        "month" => Schedule::Monthly,
+                        "year" => Schedule::Yearly,
+                        _ => return None,
+                    });
+                }
+                2
+            }
+            ("today", _) => {
+                date = Some(today);
+                1
+            }
+            ("tomorrow", _) => {
+                date = today.succ_opt();
+                1
+            }
+            ("noon", _) | ("midday", _) => {
+                time = NaiveTime::from_hms_opt(12, 0, 0);
+                1
+            }
+            ("midnight", _) => {
+                time = NaiveTime::from_hms_opt(0, 0, 0);
+                1
+            }
+            (word, _) if FILLERS.contains(&word) => 1,
+            (word, next) => {
+                if let Some(day) = parse_weekday(word) {
+                    weekday = Some(day);
+                    1
+                } else if let Some(number) = parse_month(word) {
+                    month = Some(number);
+                    1
+                } else if let Ok(parsed) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                    date = Some(parsed);
+                    1
+                } else if let Some(day) = parse_ordinal(word) {
+                    month_day = Some(day);
+                    1
+                } else if let Some((parsed, used)) = parse_time(word, next) {
+                    time = Some(parsed);
+                    used
+                } else if let Ok(day) = word.parse::<u32>() {
+                    // "march 3" or "3 march"
+                    if month.is_none() && next.and_then(parse_month).is_none() {
+                        return None;
+                    }
+                    month_day = Some(day);
+                    1
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        i += used;
+    }
+
+    // work out the first date from the parts given
+    if date.is_none() {
+        date = match (&schedule, weekday, month, month_day) {
+            (_, Some(weekday), _, _) => Some(next_weekday(today, weekday, now, time)),
+            (Some(Schedule::Monthly), None, None, Some(day)) | (None, None, None, Some(day)) => {
+                next_month_day(today, day)
+            }
+            (_, None, Some(month), Some(day)) => next_date(today, month, day),
+            _ => None,
+        };
+
+        if month_day.is_some() && date.is_none() {
+            return None;
+        }
+    }
+
+    if schedule.is_none() && date.is_none() && time.is_none() {
+        return None;
+    }
+
+    Some(ParsedSchedule {
+        schedule,
+        date,
+        time,
+    })
+}
+
+/// Reads several times of day, "8:00, 12:00 and 18:00", or a range like
+/// "every 2 hours from 8am to 6pm". Returns them as stored in a job's slot.
+pub fn parse_times_text(text: &str) -> Option<String> {
+    let cleaned = text.to_lowercase().replace(',', " ");
+    let words = cleaned.split_whitespace().collect::<Vec<&str>>();
+
+    if let ["every", rest @ ..] = &words[..] {
+        let (hours, rest) = match rest {
+            ["hour", rest @ ..] => (1, rest),
+            [number, "hours" | "hour", rest @ ..] => {
+                (number.parse::<u32>().ok().filter(|hours| *hours > 0)?, rest)
+            }
+            _ => return None,
+        };
+        let (from, rest) = match rest {
+            ["from" | "between", rest @ ..] => read_time(rest)?,
+            _ => return None,
+        };
+        let (to, rest) = match rest {
+            ["to" | "and" | "until", rest @ ..] => read_time(rest)?,
+            _ => return None,
+        };
+
+        if !rest.is_empty() || to < from {
+            return None;
+        }
+        return Some(format_time_range(hours, from, to));
+    }
+
+    let mut times = Vec::new();
+    let mut rest = &words[..];
+    while let Some(word) = rest.first() {
+        if *word == "and" {
+            rest = &rest[1..];
+            continue;
+        }
+
+        let (time, remaining) = read_time(rest)?;
+        times.push(time);
+        rest = remaining;
+    }
+
+    // a single time is answered like a picked one
+    match times.len() > 1 {
+        true => Some(format_times(&times)),
+        false => None,
+    }
+}
+
+// the time the words start with, and the words after it
+fn read_time<'a, 'b>(words: &'a [&'b str]) -> Option<(NaiveTime, &'a [&'b str])> {
+    let (time, used) = match *words.first()? {
+        "noon" | "midday" => (NaiveTime::from_hms_opt(12, 0, 0)?, 1),
+        "midnight" => (NaiveTime::from_hms_opt(0, 0, 0)?, 1),
+        word => parse_time(word, words.get(1).copied())?,
+    };
+
+    Some((time, &words[used..]))
+}
+
+pub(crate) fn parse_weekday(word: &str) -> Option<Weekday> {
+    // "mondays" reads the same as "monday"
+    let word = word.strip_suffix('s').unwrap_or(word);
+
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    let months = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+
+    months
+        .iter()
+        .position(|month| *month == word || (word.len() == 3 && month.starts_with(word)))
+        .map(|index| index as u32 + 1)
+}
+
+fn parse_ordinal(word: &str) -> Option<u32> {
+    let number = word
+        .strip_suffix("st")
+        .or(word.strip_suffix("nd"))
+        .or(word.strip_suffix("rd"))
+        .or(word.strip_suffix("th"))?;
+
+    match number.parse::<u32>() {
+        Ok(day) if (1..=31).contains(&day) => Some(day),
+        _ => None,
+    }
+}
+
+/// Reads "18:30", "9am", "9:30pm" or "9 am", the latter taking two words.
+fn parse_time(word: &str, next: Option<&str>) -> Option<(NaiveTime, usize)> {
+    let (text, suffix, used) = if let Some(text) = word.strip_suffix("am") {
+        (text, Some("am"), 1)
+    } else if let Some(text) = word.strip_suffix("pm") {
+        (text, Some("pm"), 1)
+    } else if matches!(next, Some("am") | Some("pm")) {
+        (word, next, 2)
+    } else {
+        (word, None, 1)
+    };
+
+    let (hour, minute) = match text.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // a bare number is only a time with am/pm after it
+        None if suffix.is_some() => (text.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+
+    let hour = match suffix {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| (time, used))
+}
+
+// the next `weekday` that is still ahead of `now`
+fn next_weekday(
+    today: NaiveDate,
+    weekday: Weekday,
+    now: &NaiveDateTime,
+    time: Option<NaiveTime>,
+) -> NaiveDate {
+    let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    let date = today + Days::new(ahead as u64);
+
+    match time {
+        Some(time) if date.and_time(time) <= *now => date + Days::new(7),
+        _ => date,
+    }
+}
+
+// the next month, from this one, that has `day`
+fn next_month_day(today: NaiveDate, day: u32) -> Option<NaiveDate> {
+    let first = today.with_day(1)?;
+
+    (0..12)
+        .filter_map(|i| first.checked_add_months(Months::new(i)))
+        .filter_map(|month| month.with_day(day))
+        .find(|date| *date >= today)
+}
+
+fn next_date(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    (0..8)
+        .filter_map(|i| NaiveDate::from_ymd_opt(today.year() + i, month, day))
+        .find(|date| *date >= today)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        // a wednesday
+        NaiveDate::from_ymd_opt(2026, 10, 14)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2026, month, day)
+    }
+
+    fn time(hour: u32, minute: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, minute, 0)
+    }
+
+    #[test]
+    pub fn test_parse_schedule() {
+        assert_eq!(
+            parse_schedule("every monday at 9am", &now()),
+            Some(ParsedSchedule {
+                schedule: Some(Schedule::Weekly),
+                date: date(10, 19),
+                time: time(9, 0),
+            })
+        );
+        assert_eq!(
+            parse_schedule("Tomorrow 18:30", &now()),
+            Some(ParsedSchedule {
+                schedule: None,
+                date: date(10, 15),
+                time: time(18, 30),
+            })
+        );
+        assert_eq!(
+            parse_schedule("on the 1st of every month at noon", &now()),
+            Some(ParsedSchedule {
+                schedule: Some(Schedule::Monthly),
+                date: date(11, 1),
+                time: time(12, 0),
+            })
+        );
+        assert_eq!(
+            parse_schedule("every other friday", &now()),
+            Some(ParsedSchedule {
+                schedule: Some(Schedule::Biweekly),
+                date: date(10, 16),
+                time: None,
+            })
+        );
+        assert_eq!(
+            parse_schedule("every 3 weeks on thursday", &now()),
+            Some(ParsedSchedule {
+                schedule: Some(Schedule::Interval(3, IntervalUnit::Weeks)),
+                date: date(10, 15),
+                time: None,
+            })
+        );
+        assert_eq!(
+            parse_schedule("every year on march 3 at 7:15 pm", &now()),
+            Some(ParsedSchedule {
+                schedule: Some(Schedule::Yearly),
+                date: NaiveDate::from_ymd_opt(2027, 3, 3),
+                time: time(19, 15),
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_same_weekday_already_passed() {
+        // it's wednesday 10:00, so 9am today is gone
+        let parsed = parse_schedule("every wednesday at 9am", &now()).unwrap();
+        assert_eq!(parsed.date, date(10, 21));
+
+        let parsed = parse_schedule("every wednesday at 11:00", &now()).unwrap();
+        assert_eq!(parsed.date, date(10, 14));
+    }
+
+    #[test]
+    pub fn test_parse_times_text() {
+        assert_eq!(
+            parse_times_text("8:00, 12:00 and 6pm"),
+            Some("08:00,12:00,18:00".to_owned())
+        );
+        assert_eq!(
+            parse_times_text("every 2 hours between 8 am and 18:00"),
+            Some("every-2h-08:00-18:00".to_owned())
+        );
+        assert_eq!(parse_times_text("9am"), None);
+        assert_eq!(parse_times_text("every 2 hours from 18:00 to 8:00"), None);
+    }
+
+    #[test]
+    pub fn test_not_a_schedule() {
+        assert_eq!(parse_schedule("hello there", &now()), None);
+        assert_eq!(parse_schedule("every blue moon", &now()), None);
+        assert_eq!(parse_schedule("at 25:00", &now()), None);
+        assert_eq!(parse_schedule("", &now()), None);
+    }
+}
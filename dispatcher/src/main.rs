@@ -0,0 +1,113 @@
+extern crate telegram;
+
+use chrono::{Duration, Utc};
+use event_manager::{
+    fsck, get_all_files, get_due_files, load_config, mark_dispatched, migrate_biweekly,
+    migrate_collisions, Schedule,
+};
+use std::env;
+use telegram::BotBoy;
+
+// meant to be run once a minute, e.g. from cron. After a gap it first catches
+// up on deliveries missed meanwhile, `dispatcher catch-up` does so right away.
+// `dispatcher recheck` verifies job owners are still chat admins and archives
+// jobs that have ended, run it daily.
+// `dispatcher migrate` repairs jobs stored under the old one-job-per-slot layout
+// and moves biweekly jobs to two week intervals.
+// `dispatcher fsck [--repair]` reports (and fixes) inconsistencies in the store.
+fn main() {
+    let bot = BotBoy::new();
+
+    match env::args().nth(1).as_deref() {
+        Some("recheck") => recheck(&bot),
+        Some("catch-up") => catch_up(&bot),
+        Some("fsck") => {
+            let repair = env::args().any(|arg| arg == "--repair");
+            let report = fsck(repair);
+
+            for line in report.iter() {
+                println!("{}", line);
+            }
+            println!("{} problem(s) found", report.len());
+        }
+        Some("migrate") => {
+            for line in migrate_collisions() {
+                println!("{}", line);
+            }
+            for line in migrate_biweekly(Utc::now().date_naive()) {
+                println!("{}", line);
+            }
+        }
+        _ => dispatch(&bot),
+    }
+}
+
+fn dispatch(bot: &BotBoy) {
+    let now = Utc::now();
+
+    // a minute or more was skipped, e.g. the host was down
+    let last = mark_dispatched(&now);
+    if last.is_none_or(|last| now - last > Duration::seconds(90)) {
+        catch_up(bot);
+    }
+
+    for path in get_due_files(&now.naive_utc()) {
+        match bot.deliver(&path) {
+            Ok(results) => {
+                for (chat_id, result) in results {
+                    match result {
+                        Ok(()) => println!("sent {} to {}", path, chat_id),
+                        Err(e) => println!("failed {} to {}: {}", path, chat_id, e),
+                    }
+                }
+            }
+            Err(e) => println!("failed {}: {}", path, e),
+        }
+
+        match bot.archive_if_ended(&path) {
+            Ok(true) => println!("archived {}", path),
+            Ok(false) => {
+                if let Some(next) = get_next_occurrence(&path) {
+                    println!("next {} at {}", path, next);
+                }
+            }
+            Err(e) => println!("failed to archive {}: {}", path, e),
+        }
+    }
+}
+
+// when the job at `path` is sent next, in its timezone
+fn get_next_occurrence(path: &String) -> Option<String> {
+    let config = load_config(path).ok()?;
+    let schedule = Schedule::from_file_location(path)?;
+
+    config
+        .next_occurrences(&schedule, &Utc::now(), 1)
+        .first()
+        .map(|next| next.format("%Y-%m-%d %H:%M %Z").to_string())
+}
+
+fn catch_up(bot: &BotBoy) {
+    for path in get_all_files() {
+        if let Some((missed, sent)) = bot.catch_up(&path) {
+            println!("{} missed {}, sent {} late", path, missed, sent);
+        }
+    }
+}
+
+fn recheck(bot: &BotBoy) {
+    for path in get_all_files() {
+        match bot.recheck_owner(&path) {
+            Ok(true) => println!("disabled {}", path),
+            Ok(false) => {}
+            Err(e) => println!("failed to check {}: {}", path, e),
+        }
+
+        // jobs whose end date passed without another delivery
+        match bot.archive_if_ended(&path) {
+            Ok(true) => println!("archived {}", path),
+            Ok(false) => {}
+            Err(e) => println!("failed to archive {}: {}", path, e),
+        }
+    }
+}
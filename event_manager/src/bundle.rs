@@ -0,0 +1,150 @@
+use crate::{get_last_day_of_month, parse_times, validate_template, Config, Message, Schedule};
+use chrono::Datelike;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+// bumped when the layout of a bundle changes
+const BUNDLE_VERSION: u32 = 1;
+
+/// A backup of jobs, media file ids and state included.
+#[derive(Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub jobs: Vec<BundledJob>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BundledJob {
+    pub schedule: Schedule,
+    pub job: Config,
+}
+
+/// The jobs as a single YAML document, or JSON when `json` is set.
+pub fn export_bundle(jobs: Vec<(Config, Schedule)>, json: bool) -> Result<String, String> {
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        jobs: jobs
+            .into_iter()
+            .map(|(job, schedule)| BundledJob { schedule, job })
+            .collect(),
+    };
+
+    match json {
+        true => serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string()),
+        false => serde_yaml::to_string(&bundle).map_err(|e| e.to_string()),
+    }
+}
+
+/// Reads a bundle made by export_bundle, in either format.
+pub fn parse_bundle(text: &str) -> Result<Bundle, String> {
+    let bundle = match text.trim_start().starts_with('{') {
+        true => serde_json::from_str::<Bundle>(text).map_err(|e| e.to_string()),
+        false => serde_yaml::from_str::<Bundle>(text).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("That's not a backup made with /export: {}", e))?;
+
+    match bundle.version {
+        BUNDLE_VERSION => Ok(bundle),
+        version => Err(format!("Backups of version {} can't be read.", version)),
+    }
+}
+
+impl BundledJob {
+    /// Why the job can't be scheduled as it is, if it can't.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.job.messages.is_empty() {
+            return Err("it has no messages".to_owned());
+        }
+        for message in self.job.messages.iter() {
+            if let Message::Message(text) = message {
+                validate_template(text).map_err(|e| format!("its message is invalid: {}", e))?;
+            }
+        }
+
+        if self.job.targets.is_empty() {
+            return Err("it has no chats".to_owned());
+        }
+        if let Some(timezone) = &self.job.timezone {
+            if timezone.parse::<Tz>().is_err() {
+                return Err(format!("its time zone {} is unknown", timezone));
+            }
+        }
+
+        let time = self.schedule.get_time();
+        if parse_times(time).is_none() {
+            return Err(format!("its time \"{}\" can't be read", time));
+        }
+
+        let valid = match &self.schedule {
+            Schedule::Interval { every, .. } => *every > 0,
+            Schedule::Monthly { day, .. } => (1..=31).contains(day),
+            // february 29th is fine, it's there in leap years
+            Schedule::Yearly { day, month, .. } => {
+                get_last_day_of_month(2028, month.number_from_month())
+                    .is_some_and(|last| *day >= 1 && *day as u32 <= last.day())
+            }
+            Schedule::MonthlyNthWeekday { nth, .. } => (1..=4).contains(nth) || *nth == -1,
+            _ => true,
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err("its schedule has no days to be sent on".to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{Selection, Target};
+    use chrono::Month;
+
+    #[test]
+    pub fn test_bundle() {
+        let messages = vec![
+            Message::Message("Standup in {days_until:2026-12-24} days".to_owned()),
+            Message::Photo("AgACAgIAAx0".to_owned()),
+        ];
+        let new_config = || {
+            let mut config = Config::new(
+                vec![Target::new("-100123".to_owned())],
+                messages.to_owned(),
+                Selection::RoundRobin,
+            );
+            config.occurrence_count = 3;
+            config
+        };
+        let schedule = Schedule::Yearly {
+            day: 29,
+            time: "08:00,12:00".to_owned(),
+            month: Month::February,
+        };
+
+        for json in [false, true] {
+            let text = export_bundle(vec![(new_config(), schedule.clone())], json).unwrap();
+            let bundle = parse_bundle(&text).unwrap();
+
+            assert_eq!(bundle.jobs.len(), 1);
+            assert_eq!(bundle.jobs[0].schedule, schedule);
+            assert_eq!(bundle.jobs[0].job.messages, messages);
+            assert_eq!(bundle.jobs[0].job.occurrence_count, 3);
+            assert!(bundle.jobs[0].validate().is_ok());
+        }
+
+        let invalid = BundledJob {
+            schedule: Schedule::Monthly {
+                day: 32,
+                time: "09:00".to_owned(),
+            },
+            job: new_config(),
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err("its schedule has no days to be sent on".to_owned())
+        );
+
+        assert!(parse_bundle("version: 2\njobs: []\n").is_err());
+        assert!(parse_bundle("hello").is_err());
+    }
+}
@@ -0,0 +1,741 @@
+use crate::{parse_times, Config, IntervalUnit, Message, Schedule, Selection};
+use chrono::{DateTime, Datelike, Month, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+// yearly events without an end are expanded this many years
+const YEARLY_SPAN: u32 = 10;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+const WORKDAYS: &str = "MO,TU,WE,TH,FR";
+
+// a VEVENT, with the line each part of it starts on
+struct Event {
+    line: usize,
+    properties: Vec<Property>,
+}
+
+struct Property {
+    line: usize,
+    name: String,
+    // e.g. "TZID=Europe/Berlin", empty without any
+    params: String,
+    value: String,
+}
+
+impl Event {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+}
+
+impl Property {
+    // the property as it was written, for reporting
+    fn unsupported(&self, reason: &str) -> String {
+        let params = match self.params.is_empty() {
+            true => String::new(),
+            false => format!(";{}", self.params),
+        };
+
+        format!(
+            "line {}: {}{}:{} - {}",
+            self.line, self.name, params, self.value, reason
+        )
+    }
+}
+
+/// The dates covered by the events of an iCalendar file, e.g. a holiday
+/// calendar. Only yearly repeats are followed, other repeating events
+/// count for their first date.
+pub fn parse_ics_dates(text: &str) -> Result<Vec<NaiveDate>, String> {
+    let mut dates = read_events(text)?
+        .iter()
+        .flat_map(get_event_dates)
+        .collect::<Vec<NaiveDate>>();
+
+    dates.sort();
+    dates.dedup();
+
+    match dates.is_empty() {
+        true => Err("I couldn't find any dates in that calendar.".to_owned()),
+        false => Ok(dates),
+    }
+}
+
+/// An iCalendar file with one repeating event per job and time of day,
+/// starting at its next delivery after `now`. Missing day policies and
+/// skipped weekends of anything but daily jobs have no equivalent there.
+pub fn export_ics(jobs: &[(Config, Schedule)], now: &DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//scheduler//export//EN".to_owned(),
+    ];
+
+    for (config, schedule) in jobs {
+        let times = parse_times(schedule.get_time()).unwrap_or_default();
+        let rule = get_rule(config, schedule, times.len());
+
+        // the next delivery at each time of day
+        for start in config.next_occurrences(schedule, now, times.len()) {
+            let start = start.with_timezone(&Utc);
+
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!(
+                "UID:{}-{}@scheduler",
+                config.id.as_deref().unwrap_or("job"),
+                start.format("%H%M")
+            ));
+            lines.push(format!("DTSTAMP:{}", format_ics_time(now)));
+            lines.push(format!("DTSTART:{}", format_ics_time(&start)));
+            lines.push(format!("RRULE:{}", rule));
+            if !config.skip_dates.is_empty() {
+                let skips = config
+                    .skip_dates
+                    .iter()
+                    .map(|date| format_ics_time(&date.and_time(start.time()).and_utc()))
+                    .collect::<Vec<String>>();
+                lines.push(format!("EXDATE:{}", skips.join(",")));
+            }
+
+            let text = match config.messages.first() {
+                Some(Message::Message(text)) => text.to_owned(),
+                _ => format!("Scheduled message [{}]", config.short_id()),
+            };
+            lines.push(format!(
+                "SUMMARY:{}",
+                escape(text.lines().next().unwrap_or_default())
+            ));
+            lines.push(format!("DESCRIPTION:{}", escape(&text)));
+            lines.push("END:VEVENT".to_owned());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+/// Jobs read from an iCalendar file, still without chats.
+pub struct Imported {
+    pub jobs: Vec<(Config, Schedule)>,
+    // a line for each event that couldn't be scheduled
+    pub report: Vec<String>,
+}
+
+/// Turns the repeating events of an iCalendar file into jobs without chats.
+/// Events that can't be scheduled are reported by line, e.g.
+/// "line 12: RRULE:FREQ=HOURLY - hourly repeats aren't supported".
+pub fn import_ics(text: &str, today: NaiveDate) -> Result<Imported, String> {
+    let events = read_events(text)?;
+    if events.is_empty() {
+        return Err("I couldn't find any events in that calendar.".to_owned());
+    }
+
+    let mut jobs = Vec::new();
+    let mut report = Vec::new();
+    for event in events.iter() {
+        match import_event(event, today) {
+            Ok(job) => jobs.push(job),
+            Err(line) => report.push(line),
+        }
+    }
+
+    Ok(Imported { jobs, report })
+}
+
+fn import_event(event: &Event, today: NaiveDate) -> Result<(Config, Schedule), String> {
+    let rule = event
+        .get("RRULE")
+        .ok_or(format!("line {}: the event doesn't repeat", event.line))?;
+    let start = event
+        .get("DTSTART")
+        .ok_or(format!("line {}: the event has no DTSTART", event.line))?;
+    let text = event
+        .get("DESCRIPTION")
+        .or(event.get("SUMMARY"))
+        .map(|property| unescape(&property.value))
+        .filter(|text| !text.trim().is_empty())
+        .ok_or(format!(
+            "line {}: the event has no SUMMARY to send",
+            event.line
+        ))?;
+
+    let mut config = Config::new(
+        Vec::new(),
+        vec![Message::Message(text)],
+        Selection::RoundRobin,
+    );
+
+    // the dispatcher works in UTC, so a TZID is only taken when its offset
+    // never changes, and kept for display
+    let start_time =
+        NaiveDateTime::parse_from_str(start.value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+            .map_err(|_| start.unsupported("all day events have no time to send at"))?;
+    let start_time = match start.params.strip_prefix("TZID=") {
+        Some(name) => {
+            let tz = name
+                .parse::<Tz>()
+                .map_err(|_| start.unsupported("unknown time zone"))?;
+            let utc = tz
+                .from_local_datetime(&start_time)
+                .earliest()
+                .ok_or(start.unsupported("the time doesn't exist in that time zone"))?
+                .with_timezone(&Utc);
+
+            // weekdays and days of the month would be counted on the UTC day
+            if utc.date_naive() != start_time.date() {
+                return Err(start.unsupported("the time is on another day in UTC"));
+            }
+            if has_daylight_saving(&tz, start_time.year()) {
+                return Err(start.unsupported(
+                    "daylight saving time would move it by an hour, please use a UTC time",
+                ));
+            }
+
+            config.timezone = Some(name.to_owned());
+            utc
+        }
+        None => start_time.and_utc(),
+    };
+    let date = start_time.date_naive();
+    let time = start_time.format("%H:%M").to_string();
+
+    let mut parts = Vec::new();
+    for part in rule.value.split(';').filter(|part| !part.is_empty()) {
+        match part.split_once('=') {
+            Some((key, value)) => match key {
+                "FREQ" | "INTERVAL" | "COUNT" | "UNTIL" | "BYDAY" | "BYMONTHDAY" | "BYMONTH"
+                | "BYSETPOS" => parts.push((key, value)),
+                // the first day of the week only matters to rules we don't support
+                "WKST" => {}
+                _ => return Err(rule.unsupported(&format!("{} isn't supported", key))),
+            },
+            None => return Err(rule.unsupported("the rule can't be read")),
+        }
+    }
+    let get = |key: &str| {
+        parts
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| *value)
+    };
+
+    let every = match get("INTERVAL").map(|every| every.parse::<u32>()) {
+        Some(Ok(every)) if every > 0 => every,
+        Some(_) => return Err(rule.unsupported("the interval must be a positive number")),
+        None => 1,
+    };
+    let interval = |unit| Schedule::Interval {
+        every,
+        unit,
+        anchor: date,
+        time: time.to_owned(),
+    };
+
+    let by_day = get("BYDAY");
+    let by_month_day = get("BYMONTHDAY").map(|day| day.parse::<i32>());
+    let by_set_pos = get("BYSETPOS");
+    if by_set_pos.is_some() && (by_day != Some(WORKDAYS) || by_set_pos != Some("-1")) {
+        return Err(rule.unsupported("BYSETPOS only works for the last business day"));
+    }
+
+    let schedule = match get("FREQ") {
+        Some("DAILY") => match (by_day, every) {
+            (None, 1) => Schedule::Daily { time },
+            (None, _) => interval(IntervalUnit::Days),
+            (Some(WORKDAYS), 1) => {
+                config.skip_weekends = true;
+                Schedule::Daily { time }
+            }
+            _ => return Err(rule.unsupported("daily repeats can only skip weekends")),
+        },
+        Some("WEEKLY") => {
+            let weekday = match by_day {
+                Some(day) => parse_weekday(day)
+                    .ok_or(rule.unsupported("weekly repeats need a single weekday"))?,
+                None => date.weekday(),
+            };
+
+            match every {
+                1 => Schedule::Weekly { weekday, time },
+                _ if weekday == date.weekday() => interval(IntervalUnit::Weeks),
+                _ => {
+                    return Err(
+                        rule.unsupported("repeats every few weeks must start on their weekday")
+                    )
+                }
+            }
+        }
+        Some("MONTHLY") => match (by_day, by_month_day, every) {
+            (Some(WORKDAYS), None, 1) if by_set_pos.is_some() => {
+                Schedule::MonthlyLastBusinessDay { time }
+            }
+            (Some(day), None, 1) => {
+                let (nth, weekday) = day.split_at(day.len().saturating_sub(2));
+                match (nth.parse::<i32>(), parse_weekday(weekday)) {
+                    (Ok(nth), Some(weekday)) if (1..=4).contains(&nth) || nth == -1 => {
+                        Schedule::MonthlyNthWeekday { nth, weekday, time }
+                    }
+                    _ => {
+                        return Err(rule.unsupported(
+                            "monthly weekdays must be the 1st to 4th or last of the month",
+                        ))
+                    }
+                }
+            }
+            (None, Some(Ok(-1)), 1) => Schedule::MonthlyLastDay { time },
+            (None, Some(Ok(day)), 1) if (1..=31).contains(&day) => Schedule::Monthly { day, time },
+            (None, None, 1) => Schedule::Monthly {
+                day: date.day() as i32,
+                time,
+            },
+            (None, None, _) => interval(IntervalUnit::Months),
+            (None, Some(Ok(day)), _) if day == date.day() as i32 => interval(IntervalUnit::Months),
+            _ => return Err(rule.unsupported("this monthly repeat isn't supported")),
+        },
+        Some("YEARLY") if every == 1 && by_day.is_none() => {
+            let month = match get("BYMONTH").map(|month| month.parse::<u8>()) {
+                Some(Ok(month)) => month,
+                Some(Err(_)) => return Err(rule.unsupported("several months aren't supported")),
+                None => date.month() as u8,
+            };
+            let day = match by_month_day {
+                Some(Ok(day)) if (1..=31).contains(&day) => day,
+                Some(_) => return Err(rule.unsupported("this day of the month isn't supported")),
+                None => date.day() as i32,
+            };
+
+            Schedule::Yearly {
+                month: Month::try_from(month)
+                    .map_err(|_| rule.unsupported("there is no such month"))?,
+                day,
+                time,
+            }
+        }
+        Some("YEARLY") => return Err(rule.unsupported("this yearly repeat isn't supported")),
+        Some(freq) => {
+            return Err(
+                rule.unsupported(&format!("{} repeats aren't supported", freq.to_lowercase()))
+            )
+        }
+        None => return Err(rule.unsupported("the rule has no FREQ")),
+    };
+
+    if let Some(count) = get("COUNT") {
+        config.max_deliveries = Some(
+            count
+                .parse()
+                .map_err(|_| rule.unsupported("the count must be a number"))?,
+        );
+    }
+    config.end_date = get("UNTIL").and_then(parse_ics_date);
+    // nothing is sent before the event starts
+    if date > today {
+        config.start_date = Some(date);
+    }
+
+    for exdate in event.properties.iter().filter(|p| p.name == "EXDATE") {
+        config
+            .skip_dates
+            .extend(exdate.value.split(',').filter_map(parse_ics_date));
+    }
+
+    Ok((config, schedule))
+}
+
+fn read_events(text: &str) -> Result<Vec<Event>, String> {
+    if !text.contains("BEGIN:VCALENDAR") {
+        return Err("That doesn't look like an iCalendar (.ics) file.".to_owned());
+    }
+
+    let mut events = Vec::new();
+    let mut event: Option<Event> = None;
+
+    for (line, content) in unfold(text) {
+        let (name, value) = match content.split_once(':') {
+            Some((name, value)) => (name, value.trim()),
+            None => continue,
+        };
+        // parameters like ";VALUE=DATE" follow the name
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+
+        match (name, event.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => {
+                event = Some(Event {
+                    line,
+                    properties: Vec::new(),
+                })
+            }
+            ("END", Some(_)) if value == "VEVENT" => events.push(event.take().unwrap()),
+            (_, Some(event)) => event.properties.push(Property {
+                line,
+                name: name.to_owned(),
+                params: params.to_owned(),
+                value: value.to_owned(),
+            }),
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
+
+// long lines continue on the next one after a space or tab, numbered from 1
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ => lines.push((number + 1, line.trim_end().to_owned())),
+        }
+    }
+
+    lines
+}
+
+// lines are at most 75 bytes long, the rest continues on the next
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') | Some('N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            },
+            (c, false) => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn format_ics_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// "MO" to "SU"
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|(name, _)| *name == day)
+        .map(|(_, weekday)| *weekday)
+}
+
+fn get_ics_weekday(weekday: Weekday) -> &'static str {
+    WEEKDAYS
+        .iter()
+        .find(|(_, day)| *day == weekday)
+        .map(|(name, _)| *name)
+        .unwrap_or("MO")
+}
+
+// whether the UTC offset of `tz` changes during `year`
+fn has_daylight_saving(tz: &Tz, year: i32) -> bool {
+    let offset = |month| {
+        NaiveDate::from_ymd_opt(year, month, 1).map(|date| tz.offset_from_utc_date(&date).fix())
+    };
+
+    offset(1) != offset(7)
+}
+
+fn get_rule(config: &Config, schedule: &Schedule, times: usize) -> String {
+    let mut rule = match schedule {
+        Schedule::Daily { .. } if config.skip_weekends => format!("FREQ=DAILY;BYDAY={}", WORKDAYS),
+        Schedule::Daily { .. } => "FREQ=DAILY".to_owned(),
+        Schedule::Weekly { weekday, .. } => {
+            format!("FREQ=WEEKLY;BYDAY={}", get_ics_weekday(*weekday))
+        }
+        Schedule::Interval { every, unit, .. } => format!(
+            "FREQ={};INTERVAL={}",
+            match unit {
+                IntervalUnit::Days => "DAILY",
+                IntervalUnit::Weeks => "WEEKLY",
+                IntervalUnit::Months => "MONTHLY",
+            },
+            every
+        ),
+        Schedule::Monthly { day, .. } => format!("FREQ=MONTHLY;BYMONTHDAY={}", day),
+        Schedule::Yearly { day, month, .. } => format!(
+            "FREQ=YEARLY;BYMONTH={};BYMONTHDAY={}",
+            month.number_from_month(),
+            day
+        ),
+        Schedule::MonthlyLastDay { .. } => "FREQ=MONTHLY;BYMONTHDAY=-1".to_owned(),
+        Schedule::MonthlyNthWeekday { nth, weekday, .. } => {
+            format!("FREQ=MONTHLY;BYDAY={}{}", nth, get_ics_weekday(*weekday))
+        }
+        Schedule::MonthlyLastBusinessDay { .. } => {
+            format!("FREQ=MONTHLY;BYDAY={};BYSETPOS=-1", WORKDAYS)
+        }
+    };
+
+    // a count is per event, which only fits jobs sent once a day
+    match (config.max_deliveries, config.end_date) {
+        (Some(max), _) if times == 1 => rule.push_str(&format!(
+            ";COUNT={}",
+            max.saturating_sub(config.occurrence_count)
+        )),
+        (_, Some(end)) => {
+            // the end date is a day in the job's time zone
+            let end = end.and_hms_opt(23, 59, 59).unwrap();
+            let until = config
+                .get_timezone()
+                .from_local_datetime(&end)
+                .latest()
+                .map(|until| until.with_timezone(&Utc))
+                .unwrap_or(end.and_utc());
+            rule.push_str(&format!(";UNTIL={}", format_ics_time(&until)))
+        }
+        _ => {}
+    }
+
+    rule
+}
+
+// "20261225" or "20261225T090000Z"
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn get_event_dates(event: &Event) -> Vec<NaiveDate> {
+    let start = match event
+        .get("DTSTART")
+        .and_then(|start| parse_ics_date(&start.value))
+    {
+        Some(start) => start,
+        None => return Vec::new(),
+    };
+
+    // the end of an all day event is the day after it
+    let days = match event
+        .get("DTEND")
+        .and_then(|end| parse_ics_date(&end.value))
+    {
+        Some(end) if end > start => (end - start).num_days() as u64,
+        _ => 1,
+    };
+    let span = |first: NaiveDate| {
+        first
+            .iter_days()
+            .take(days as usize)
+            .collect::<Vec<NaiveDate>>()
+    };
+
+    let rule = match event.get("RRULE") {
+        Some(rule) if rule.value.contains("FREQ=YEARLY") => &rule.value,
+        _ => return span(start),
+    };
+
+    let mut count = YEARLY_SPAN;
+    let mut until = None;
+    for part in rule.split(';') {
+        match part.split_once('=') {
+            Some(("COUNT", value)) => count = value.parse().unwrap_or(count),
+            Some(("UNTIL", value)) => until = parse_ics_date(value),
+            _ => {}
+        }
+    }
+
+    (0..count)
+        .filter_map(|i| start.with_year(start.year() + i as i32))
+        .take_while(|first| until.is_none_or(|until| *first <= until))
+        .flat_map(span)
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_parse_ics_dates() {
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Christmas\r\n\
+            DTSTART;VALUE=DATE:20261225\r\n\
+            DTEND;VALUE=DATE:20261227\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:New Year\r\n\
+            DTSTART;VALUE=DATE:20270101\r\n\
+            RRULE:FREQ=YEARLY;\r\n COUNT=2\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let dates = parse_ics_dates(calendar)
+            .unwrap()
+            .iter()
+            .map(|date| date.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            dates,
+            ["2026-12-25", "2026-12-26", "2027-01-01", "2028-01-01"]
+        );
+
+        assert!(parse_ics_dates("2026-12-25").is_err());
+    }
+
+    #[test]
+    pub fn test_import_export_ics() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Standup\\, room 2\r\n\
+            DTSTART:20261012T090000Z\r\n\
+            RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Rent\r\n\
+            DTSTART;TZID=Asia/Tokyo:20261030T180000\r\n\
+            RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=12\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Water the plants\r\n\
+            DTSTART:20261012T080000Z\r\n\
+            RRULE:FREQ=HOURLY;INTERVAL=4\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let Imported { jobs, report } = import_ics(calendar, today).unwrap();
+        assert_eq!(
+            report,
+            ["line 15: RRULE:FREQ=HOURLY;INTERVAL=4 - hourly repeats aren't supported"]
+        );
+        assert_eq!(
+            jobs.iter()
+                .map(|(_, schedule)| schedule.to_owned())
+                .collect::<Vec<Schedule>>(),
+            [
+                Schedule::Daily {
+                    time: "09:00".to_owned()
+                },
+                Schedule::MonthlyNthWeekday {
+                    nth: -1,
+                    weekday: Weekday::Fri,
+                    time: "09:00".to_owned()
+                },
+            ]
+        );
+        assert!(jobs[0].0.skip_weekends);
+        assert_eq!(jobs[1].0.max_deliveries, Some(12));
+        assert_eq!(jobs[1].0.start_date, NaiveDate::from_ymd_opt(2026, 10, 30));
+        assert!(
+            matches!(&jobs[0].0.messages[0], Message::Message(text) if text == "Standup, room 2")
+        );
+
+        let now = today.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        let exported = export_ics(&jobs, &now);
+        assert!(exported
+            .contains("DTSTART:20261015T090000Z\r\nRRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\r\n"));
+        assert!(exported
+            .contains("DTSTART:20261030T090000Z\r\nRRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=12\r\n"));
+
+        let again = import_ics(&exported, today).unwrap();
+        assert!(again.report.is_empty());
+        assert_eq!(again.jobs[1].1, jobs[1].1);
+    }
+
+    #[test]
+    pub fn test_import_ics_near_midnight() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let calendar = |start: &str| {
+            format!(
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Week ahead\n\
+                DTSTART;TZID={}\nRRULE:FREQ=WEEKLY;BYDAY=MO\nEND:VEVENT\nEND:VCALENDAR\n",
+                start
+            )
+        };
+
+        // monday 00:30 in Berlin is still sunday in UTC
+        let imported = import_ics(&calendar("Europe/Berlin:20261012T003000"), today).unwrap();
+        assert!(imported.jobs.is_empty());
+        assert_eq!(
+            imported.report,
+            ["line 4: DTSTART;TZID=Europe/Berlin:20261012T003000 - the time is on another day in UTC"]
+        );
+
+        // sent at a fixed UTC time, it would be an hour off half the year
+        let imported = import_ics(&calendar("Europe/Berlin:20261012T023000"), today).unwrap();
+        assert!(imported.jobs.is_empty());
+        assert_eq!(
+            imported.report,
+            ["line 4: DTSTART;TZID=Europe/Berlin:20261012T023000 - daylight saving time would move it by an hour, please use a UTC time"]
+        );
+
+        // no daylight saving time to be off by
+        let imported = import_ics(&calendar("Asia/Tokyo:20261012T093000"), today).unwrap();
+        assert_eq!(
+            imported.jobs[0].1,
+            Schedule::Weekly {
+                weekday: Weekday::Mon,
+                time: "00:30".to_owned()
+            }
+        );
+        assert_eq!(imported.jobs[0].0.timezone, Some("Asia/Tokyo".to_owned()));
+    }
+
+    #[test]
+    pub fn test_export_until_in_time_zone() {
+        let mut config = Config::new(
+            Vec::new(),
+            vec![Message::Message("Standup".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.end_date = NaiveDate::from_ymd_opt(2026, 12, 31);
+        let schedule = Schedule::Daily {
+            time: "09:00".to_owned(),
+        };
+        assert_eq!(
+            get_rule(&config, &schedule, 1),
+            "FREQ=DAILY;UNTIL=20261231T235959Z"
+        );
+
+        // the last day ends nine hours earlier in Tokyo
+        config.timezone = Some("Asia/Tokyo".to_owned());
+        assert_eq!(
+            get_rule(&config, &schedule, 1),
+            "FREQ=DAILY;UNTIL=20261231T145959Z"
+        );
+    }
+}
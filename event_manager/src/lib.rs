@@ -1,19 +1,483 @@
-use chrono::{Month, Weekday};
-use serde::{Deserialize, Serialize};
+use chrono::{
+    DateTime, Datelike, Days, Month, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use fs2::FileExt;
+use rand::seq::SliceRandom;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use std::{
-    fs::{canonicalize, create_dir_all, read_link, read_to_string, remove_file, File},
+    cell::RefCell,
+    fmt,
+    fs::{
+        canonicalize, create_dir_all, hard_link, read_link, read_to_string, remove_file, rename,
+        File, OpenOptions,
+    },
     io::Write,
     os::unix::fs::symlink,
-    path::Path,
+    path::{Path, PathBuf},
 };
+use ulid::Ulid;
+
+mod bundle;
+mod ics;
+mod template;
+
+pub use bundle::{export_bundle, parse_bundle, Bundle, BundledJob};
+pub use ics::{export_ics, import_ics, parse_ics_dates, Imported};
+pub use template::{render_template, uses_chat_title, validate_template, TemplateContext};
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
+    // stable across renumbering, older files get one when first listed
+    #[serde(default)]
+    pub id: Option<String>,
+
+    // older files hold a single `chat_id`
+    #[serde(alias = "chat_id", deserialize_with = "one_or_many_targets")]
+    pub targets: Vec<Target>,
+
+    // older files hold a single `message`
+    #[serde(alias = "message", deserialize_with = "one_or_many")]
+    pub messages: Vec<Message>,
+
+    #[serde(default)]
+    pub selection: Selection,
+
+    // next index for round robin, indices not yet sent for shuffle.
+    #[serde(default)]
+    pub cursor: usize,
+    #[serde(default)]
+    pub remaining: Vec<usize>,
+
+    // IANA name, e.g. Europe/Berlin. UTC when not set, as for every job made
+    // in the wizard, whose times are UTC too. Imported calendars keep theirs.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    #[serde(default)]
+    pub occurrence_count: u32,
+
+    // user id of whoever scheduled the job
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    // reason the job was switched off, it is skipped while set
+    #[serde(default)]
+    pub disabled: Option<String>,
+
+    // what to do in months without the job's day, e.g. the 31st
+    #[serde(default)]
+    pub missing_day: MissingDay,
+
+    // optional bounds, dates are in the job's timezone and inclusive
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub max_deliveries: Option<u32>,
+
+    // days nothing is sent on, e.g. holidays
+    #[serde(default)]
+    pub skip_weekends: bool,
+    #[serde(default)]
+    pub skip_dates: Vec<NaiveDate>,
+
+    // deliveries due after the later of these were missed if not sent
+    #[serde(default)]
+    pub last_fired_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub caught_up_at: Option<DateTime<Utc>>,
+
+    // what to do with missed deliveries, older than the grace window they're dropped
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    #[serde(default)]
+    pub grace_minutes: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Target {
     pub chat_id: String,
-    pub message: Message,
+
+    // forum topic of a supergroup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<i64>,
+
+    // for display only, as resolved when the job was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl Target {
+    pub fn new(chat_id: String) -> Target {
+        Target {
+            chat_id,
+            thread_id: None,
+            title: None,
+        }
+    }
+
+    pub fn in_thread(chat_id: String, thread_id: i64) -> Target {
+        Target {
+            chat_id,
+            thread_id: Some(thread_id),
+            title: None,
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.title.as_ref().unwrap_or(&self.chat_id);
+
+        match self.thread_id {
+            Some(thread_id) => write!(f, "{} (topic {})", name, thread_id),
+            None => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Selection {
+    #[default]
+    RoundRobin,
+    Random,
+    Shuffle,
+}
+
+/// When a monthly or yearly job falls on a day the month doesn't have.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum MissingDay {
+    #[default]
+    Skip,
+    LastDay,
+    NextDay,
+}
+
+/// What to do with deliveries missed while the dispatcher wasn't running.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum CatchUp {
+    // one late message for however many were missed
+    #[default]
+    Once,
+    All,
+    Skip,
+}
+
+// how late a missed delivery may still be sent, in minutes
+pub const DEFAULT_GRACE_MINUTES: u32 = 180;
+
+/// Deliveries of a job missed while the dispatcher was down.
+pub struct Missed {
+    pub config: Config,
+    // oldest first
+    pub due: Vec<DateTime<Utc>>,
+    // how many to send late, by the job's policy and how many of the latest
+    // are within its grace window
+    pub send: usize,
+}
+
+impl MissingDay {
+    /// The date a job on `day` fires in the given month, if at all.
+    pub fn resolve(self, year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+            return Some(date);
+        }
+
+        let last = get_last_day_of_month(year, month)?;
+        match self {
+            MissingDay::Skip => None,
+            MissingDay::LastDay => Some(last),
+            MissingDay::NextDay => last.succ_opt(),
+        }
+    }
+}
+
+pub fn get_last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+
+    first
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Message>, D::Error> {
+    let value = serde_yaml::Value::deserialize(deserializer)?;
+
+    match value {
+        serde_yaml::Value::Sequence(messages) => messages
+            .into_iter()
+            .map(|message| serde_yaml::from_value(tag_message(message)))
+            .collect::<Result<Vec<Message>, _>>()
+            .map_err(D::Error::custom),
+        value => serde_yaml::from_value(tag_message(value))
+            .map(|message| vec![message])
+            .map_err(D::Error::custom),
+    }
+}
+
+// JSON writes a message as {"Photo": "id"} where YAML has !Photo id
+fn tag_message(value: serde_yaml::Value) -> serde_yaml::Value {
+    match value {
+        serde_yaml::Value::Mapping(map) if map.len() == 1 => {
+            let (key, value) = map.into_iter().next().unwrap();
+            match key {
+                serde_yaml::Value::String(tag) => {
+                    serde_yaml::Value::Tagged(Box::new(serde_yaml::value::TaggedValue {
+                        tag: serde_yaml::value::Tag::new(tag),
+                        value,
+                    }))
+                }
+                key => serde_yaml::Value::Mapping([(key, value)].into_iter().collect()),
+            }
+        }
+        other => other,
+    }
+}
+
+fn one_or_many_targets<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Target>, D::Error> {
+    match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::String(chat_id) => Ok(vec![Target::new(chat_id)]),
+        value => serde_yaml::from_value(value).map_err(D::Error::custom),
+    }
+}
+
+impl Config {
+    pub fn new(targets: Vec<Target>, messages: Vec<Message>, selection: Selection) -> Config {
+        Config {
+            id: Some(new_job_id()),
+            targets,
+            messages,
+            selection,
+            cursor: 0,
+            remaining: Vec::new(),
+            timezone: None,
+            occurrence_count: 0,
+            owner: None,
+            disabled: None,
+            missing_day: MissingDay::Skip,
+            start_date: None,
+            end_date: None,
+            max_deliveries: None,
+            skip_weekends: false,
+            skip_dates: Vec::new(),
+            last_fired_at: None,
+            caught_up_at: None,
+            catch_up: CatchUp::Once,
+            grace_minutes: None,
+        }
+    }
+
+    /// Whether nothing is sent on `date`, in the job's timezone.
+    pub fn is_skipped(&self, date: NaiveDate) -> bool {
+        (self.skip_weekends && matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            || self.skip_dates.contains(&date)
+    }
+
+    /// e.g. "weekends, 12 dates"
+    pub fn get_skips_display(&self) -> Option<String> {
+        let mut skips = Vec::new();
+
+        if self.skip_weekends {
+            skips.push("weekends".to_owned());
+        }
+        match self.skip_dates.len() {
+            0 => {}
+            1 => skips.push(self.skip_dates[0].to_string()),
+            count => skips.push(format!("{} dates", count)),
+        }
+
+        match skips.is_empty() {
+            true => None,
+            false => Some(skips.join(", ")),
+        }
+    }
+
+    /// Why the job won't be sent again from `today` on, if it won't.
+    pub fn get_end_reason(&self, today: NaiveDate) -> Option<String> {
+        if let Some(max) = self.max_deliveries {
+            if self.occurrence_count >= max {
+                return Some(format!("it was sent {} times", max));
+            }
+        }
+
+        match self.end_date {
+            Some(end) if today > end => Some(format!("its end date {} has passed", end)),
+            _ => None,
+        }
+    }
+
+    /// How missed deliveries are handled, None for the default of sending one late.
+    pub fn get_catch_up_display(&self) -> Option<String> {
+        match (self.catch_up, self.grace_minutes) {
+            (CatchUp::Once, None) => None,
+            (CatchUp::Once, Some(grace)) => Some(format!("one sent up to {} minutes late", grace)),
+            (CatchUp::All, None) => Some("all sent late".to_owned()),
+            (CatchUp::All, Some(grace)) => Some(format!("all sent up to {} minutes late", grace)),
+            (CatchUp::Skip, _) => Some("skipped".to_owned()),
+        }
+    }
+
+    /// Deliveries on `schedule` due after the job was last sent or caught up,
+    /// and before the minute of `now`, which is dispatched as usual.
+    pub fn missed_occurrences(
+        &self,
+        schedule: &Schedule,
+        now: &DateTime<Utc>,
+    ) -> Vec<DateTime<Utc>> {
+        let since = match self.last_fired_at.max(self.caught_up_at) {
+            Some(since) => since,
+            None => return Vec::new(),
+        };
+        let minute = now.with_second(0).and_then(|now| now.with_nanosecond(0));
+
+        self.occurrences(schedule, since)
+            .take_while(|occurrence| minute.is_some_and(|minute| *occurrence < minute))
+            .collect()
+    }
+
+    /// The bounds of the job, e.g. "from 2026-11-01, until 2026-12-31, 3 of 10 sent".
+    pub fn get_limits_display(&self) -> Option<String> {
+        let mut limits = Vec::new();
+
+        if let Some(start) = self.start_date {
+            limits.push(format!("from {}", start));
+        }
+        if let Some(end) = self.end_date {
+            limits.push(format!("until {}", end));
+        }
+        if let Some(max) = self.max_deliveries {
+            limits.push(format!(
+                "{} of {} sent",
+                self.occurrence_count.min(max),
+                max
+            ));
+        }
+
+        match limits.is_empty() {
+            true => None,
+            false => Some(limits.join(", ")),
+        }
+    }
+
+    /// The part of the id shown to users, enough to tell a user's jobs apart.
+    pub fn short_id(&self) -> String {
+        match &self.id {
+            Some(id) => id[id.len().saturating_sub(8)..].to_owned(),
+            None => String::from("?"),
+        }
+    }
+
+    /// Whether `reference` is this job's full or short id.
+    pub fn has_id(&self, reference: &str) -> bool {
+        match &self.id {
+            Some(id) => {
+                let reference = reference.to_lowercase();
+                *id == reference || (reference.len() >= 8 && id.ends_with(&reference))
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_timezone(&self) -> Tz {
+        match &self.timezone {
+            Some(name) => name.parse::<Tz>().unwrap_or(Tz::UTC),
+            None => Tz::UTC,
+        }
+    }
+
+    /// The next `n` deliveries of the job on `schedule` after `after`, shown in
+    /// the job's timezone. Slots are UTC, as the dispatcher matches them, while
+    /// start, end and skipped dates are the local dates take_message checks.
+    pub fn next_occurrences(
+        &self,
+        schedule: &Schedule,
+        after: &DateTime<Utc>,
+        n: usize,
+    ) -> Vec<DateTime<Tz>> {
+        let tz = self.get_timezone();
+
+        self.occurrences(schedule, *after)
+            .take(n)
+            .map(|occurrence| occurrence.with_timezone(&tz))
+            .collect()
+    }
+
+    // the deliveries after `after`, earliest first, up to the job's maximum
+    fn occurrences<'a>(
+        &'a self,
+        schedule: &'a Schedule,
+        after: DateTime<Utc>,
+    ) -> impl Iterator<Item = DateTime<Utc>> + 'a {
+        let remaining = match self.max_deliveries {
+            Some(max) => max.saturating_sub(self.occurrence_count) as usize,
+            None => usize::MAX,
+        };
+
+        schedule
+            .find_occurrences(after, move |occurrence| {
+                let today = self.local_time(occurrence).date();
+                schedule.is_due_on(occurrence.date_naive(), self.missing_day)
+                    && self.start_date.is_none_or(|start| today >= start)
+                    && self.end_date.is_none_or(|end| today <= end)
+                    && !self.is_skipped(today)
+            })
+            .take(remaining)
+    }
+
+    /// Wall clock time of `now` in the job's timezone.
+    pub fn local_time(&self, now: &DateTime<Utc>) -> NaiveDateTime {
+        now.with_timezone(&self.get_timezone()).naive_local()
+    }
+
+    /// Fills in the placeholders of a text message for a delivery at `now`.
+    pub fn render(&self, message: Message, now: &DateTime<Utc>, chat_title: &str) -> Message {
+        match message {
+            Message::Message(text) => Message::Message(render_template(
+                &text,
+                &TemplateContext {
+                    now: self.local_time(now),
+                    occurrence_count: self.occurrence_count,
+                    chat_title: chat_title.to_owned(),
+                },
+            )),
+            other => other,
+        }
+    }
+
+    /// Picks the message for the next delivery and advances the selection state.
+    /// The caller is responsible for persisting the config afterwards.
+    pub fn next_message(&mut self) -> Option<Message> {
+        if self.messages.is_empty() {
+            return None;
+        }
+
+        let count = self.messages.len();
+        let index = match self.selection {
+            Selection::RoundRobin => {
+                let index = self.cursor % count;
+                self.cursor = (index + 1) % count;
+                index
+            }
+            Selection::Random => rand::random::<usize>() % count,
+            Selection::Shuffle => {
+                self.remaining.retain(|i| *i < count);
+                if self.remaining.is_empty() {
+                    self.remaining = (0..count).collect();
+                    self.remaining.shuffle(&mut rand::thread_rng());
+                }
+                self.remaining.pop().unwrap()
+            }
+        };
+
+        self.messages.get(index).cloned()
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub enum Message {
     Message(String),
     Photo(String),
@@ -23,6 +487,7 @@ pub enum Message {
     Voice(String),
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub enum Schedule {
     Daily {
         time: String,
@@ -31,10 +496,12 @@ pub enum Schedule {
         weekday: Weekday,
         time: String,
     },
-    BiWeekly {
-        weekday: Weekday,
+    // every `every` days, weeks or months, counted from `anchor`
+    Interval {
+        every: u32,
+        unit: IntervalUnit,
+        anchor: NaiveDate,
         time: String,
-        odd: bool,
     },
     Monthly {
         day: i32,
@@ -45,6 +512,125 @@ pub enum Schedule {
         time: String,
         month: Month,
     },
+    MonthlyLastDay {
+        time: String,
+    },
+    // `nth` is 1 to 4, or -1 for the last one of the month
+    MonthlyNthWeekday {
+        nth: i32,
+        weekday: Weekday,
+        time: String,
+    },
+    // the last monday to friday of the month
+    MonthlyLastBusinessDay {
+        time: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum IntervalUnit {
+    Days,
+    Weeks,
+    Months,
+}
+
+impl IntervalUnit {
+    pub fn parse(unit: &str) -> Option<IntervalUnit> {
+        match unit.trim_end_matches('s') {
+            "day" => Some(IntervalUnit::Days),
+            "week" => Some(IntervalUnit::Weeks),
+            "month" => Some(IntervalUnit::Months),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IntervalUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntervalUnit::Days => write!(f, "days"),
+            IntervalUnit::Weeks => write!(f, "weeks"),
+            IntervalUnit::Months => write!(f, "months"),
+        }
+    }
+}
+
+/// Whether an interval job is due on `date`. Like is the case for monthly
+/// jobs, Some(policy) means only if its day is missing and it has that policy.
+fn is_interval_due(
+    every: u32,
+    unit: IntervalUnit,
+    anchor: NaiveDate,
+    date: NaiveDate,
+) -> Option<Option<MissingDay>> {
+    if date < anchor || every == 0 {
+        return None;
+    }
+
+    let days = (date - anchor).num_days();
+    match unit {
+        IntervalUnit::Days if days % every as i64 == 0 => return Some(None),
+        IntervalUnit::Weeks if days % (7 * every as i64) == 0 => return Some(None),
+        IntervalUnit::Months => {}
+        _ => return None,
+    }
+
+    // the month of `date`, and the one before for jobs moved to the 1st
+    let months = [Some(date), date.pred_opt().filter(|_| date.day() == 1)];
+    for month in months.into_iter().flatten() {
+        let passed =
+            (month.year() - anchor.year()) * 12 + month.month() as i32 - anchor.month() as i32;
+        if passed < 0 || passed % every as i32 != 0 {
+            continue;
+        }
+
+        match NaiveDate::from_ymd_opt(month.year(), month.month(), anchor.day()) {
+            Some(due) if due == date => return Some(None),
+            Some(_) => {}
+            None => {
+                let last = get_last_day_of_month(month.year(), month.month())?;
+                if last == date {
+                    return Some(Some(MissingDay::LastDay));
+                }
+                if last.succ_opt() == Some(date) {
+                    return Some(Some(MissingDay::NextDay));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn get_nth_display(nth: i32) -> String {
+    match nth {
+        1 => String::from("first"),
+        2 => String::from("second"),
+        3 => String::from("third"),
+        4 => String::from("fourth"),
+        _ => String::from("last"),
+    }
+}
+
+/// The `nth` `weekday` of the month, counting from its end when `nth` is negative.
+pub fn get_nth_weekday(year: i32, month: u32, nth: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if nth < 0 {
+        let last = get_last_day_of_month(year, month)?;
+        let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+
+        return last.checked_sub_days(Days::new(back as u64 + 7 * (-nth - 1) as u64));
+    }
+
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth as u8)
+}
+
+pub fn get_last_business_day(year: i32, month: u32) -> Option<NaiveDate> {
+    let mut date = get_last_day_of_month(year, month)?;
+    while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        date = date.pred_opt()?;
+    }
+
+    Some(date)
 }
 
 fn get_weekday_display(weekday: Weekday) -> String {
@@ -60,83 +646,310 @@ fn get_weekday_display(weekday: Weekday) -> String {
 }
 
 impl Schedule {
-    pub fn get_file_location(self, u_id: &String) -> String {
+    /// The schedule of the job stored at `path`, the reverse of get_file_location.
+    /// None for paths outside the schedule and older biweekly jobs.
+    pub fn from_file_location(path: &str) -> Option<Schedule> {
+        let parts = path.split('/').collect::<Vec<&str>>();
+        let base = parts.iter().position(|part| *part == "recurring")?;
+        // without the file name
+        let parts = &parts[base + 1..parts.len().checked_sub(1)?];
+        let time = parts.last()?.to_string();
+
+        match parts {
+            ["daily", _] => Some(Schedule::Daily { time }),
+            ["weekly", weekday, _] => Some(Schedule::Weekly {
+                weekday: weekday.parse().ok()?,
+                time,
+            }),
+            ["interval", unit, every, anchor, _] => Some(Schedule::Interval {
+                every: every.parse().ok()?,
+                unit: IntervalUnit::parse(unit)?,
+                anchor: NaiveDate::parse_from_str(anchor, "%Y-%m-%d").ok()?,
+                time,
+            }),
+            ["monthly", "last-day", _] => Some(Schedule::MonthlyLastDay { time }),
+            ["monthly", "last-business-day", _] => Some(Schedule::MonthlyLastBusinessDay { time }),
+            ["monthly", rule, _] => match rule.parse::<i32>() {
+                Ok(day) => Some(Schedule::Monthly { day, time }),
+                // e.g. "second-tuesday"
+                Err(_) => {
+                    let (nth, weekday) = rule.split_once('-')?;
+                    Some(Schedule::MonthlyNthWeekday {
+                        nth: [1, 2, 3, 4, -1]
+                            .into_iter()
+                            .find(|n| get_nth_display(*n) == nth)?,
+                        weekday: weekday.parse().ok()?,
+                        time,
+                    })
+                }
+            },
+            ["yearly", month, day, _] => Some(Schedule::Yearly {
+                month: Month::try_from(month.parse::<u8>().ok()?).ok()?,
+                day: day.parse().ok()?,
+                time,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The times of day of the schedule, see parse_times.
+    pub fn get_time(&self) -> &String {
+        match self {
+            Schedule::Daily { time }
+            | Schedule::Weekly { time, .. }
+            | Schedule::Interval { time, .. }
+            | Schedule::Monthly { time, .. }
+            | Schedule::Yearly { time, .. }
+            | Schedule::MonthlyLastDay { time }
+            | Schedule::MonthlyNthWeekday { time, .. }
+            | Schedule::MonthlyLastBusinessDay { time } => time,
+        }
+    }
+
+    /// Whether the job is sent on `date` at all, with `missing_day` deciding
+    /// about days its month doesn't have.
+    pub fn is_due_on(&self, date: NaiveDate, missing_day: MissingDay) -> bool {
+        let (year, month) = (date.year(), date.month());
+
+        match self {
+            Schedule::Daily { .. } => true,
+            Schedule::Weekly { weekday, .. } => date.weekday() == *weekday,
+            Schedule::Interval {
+                every,
+                unit,
+                anchor,
+                ..
+            } => match is_interval_due(*every, *unit, *anchor, date) {
+                Some(Some(policy)) => policy == missing_day,
+                Some(None) => true,
+                None => false,
+            },
+            // moved to the 1st, the day may be from the month before
+            Schedule::Monthly { day, .. } => [Some(date), date.pred_opt()]
+                .into_iter()
+                .flatten()
+                .any(|month| {
+                    missing_day.resolve(month.year(), month.month(), *day as u32) == Some(date)
+                }),
+            Schedule::Yearly { day, month, .. } => {
+                missing_day.resolve(year, month.number_from_month(), *day as u32) == Some(date)
+            }
+            Schedule::MonthlyLastDay { .. } => get_last_day_of_month(year, month) == Some(date),
+            Schedule::MonthlyNthWeekday { nth, weekday, .. } => {
+                get_nth_weekday(year, month, *nth, *weekday) == Some(date)
+            }
+            Schedule::MonthlyLastBusinessDay { .. } => {
+                get_last_business_day(year, month) == Some(date)
+            }
+        }
+    }
+
+    /// The first `n` times after `after` the job is sent, shown in `tz`.
+    /// Slots are matched against UTC by the dispatcher, so that's what they're
+    /// worked out in. Days missing from a month are skipped.
+    pub fn next_occurrences(&self, after: &DateTime<Utc>, tz: &Tz, n: usize) -> Vec<DateTime<Tz>> {
+        self.find_occurrences(*after, |occurrence| {
+            self.is_due_on(occurrence.date_naive(), MissingDay::Skip)
+        })
+        .take(n)
+        .map(|occurrence| occurrence.with_timezone(tz))
+        .collect()
+    }
+
+    // the times after `after` that `wanted` agrees to, earliest first
+    fn find_occurrences<'a, F: Fn(&DateTime<Utc>) -> bool + 'a>(
+        &self,
+        after: DateTime<Utc>,
+        wanted: F,
+    ) -> impl Iterator<Item = DateTime<Utc>> + 'a {
+        let times = parse_times(self.get_time()).unwrap_or_default();
+
+        // plenty for a few february 29ths
+        after
+            .date_naive()
+            .iter_days()
+            .take(366 * 40)
+            .flat_map(move |date| {
+                times
+                    .clone()
+                    .into_iter()
+                    .map(move |time| date.and_time(time).and_utc())
+            })
+            .filter(move |occurrence| *occurrence > after && wanted(occurrence))
+    }
+
+    /// Path of the schedule file called `name` in the slot of this schedule.
+    /// Jobs are named by their id, older ones by their owner's user id.
+    pub fn get_file_location(self, name: &String) -> String {
         match self {
             Schedule::Daily { time } => {
-                format!("/mnt/data/recurring/daily/{}/{}", &time, &u_id)
+                format!("{}/recurring/daily/{}/{}", data_root(), &time, &name)
             }
 
             Schedule::Weekly { weekday, time } => {
                 format!(
-                    "/mnt/data/recurring/weekly/{}/{}/{}",
+                    "{}/recurring/weekly/{}/{}/{}",
+                    data_root(),
                     &get_weekday_display(weekday),
                     &time,
-                    &u_id
+                    &name
                 )
             }
 
-            Schedule::BiWeekly { weekday, time, odd } => {
-                let odd_string = if odd { "1" } else { "0" };
+            Schedule::Interval {
+                every,
+                unit,
+                anchor,
+                time,
+            } => {
                 format!(
-                    "/mnt/data/recurring/biweekly/{}/{}/{}/{}",
-                    &odd_string,
-                    &get_weekday_display(weekday),
+                    "{}/recurring/interval/{}/{}/{}/{}/{}",
+                    data_root(),
+                    &unit,
+                    &every,
+                    &anchor.format("%Y-%m-%d"),
                     &time,
-                    &u_id
+                    &name
                 )
             }
 
             Schedule::Monthly { day, time } => {
-                format!("/mnt/data/recurring/monthly/{}/{}/{}", &day, &time, &u_id)
+                format!(
+                    "{}/recurring/monthly/{}/{}/{}",
+                    data_root(),
+                    &day,
+                    &time,
+                    &name
+                )
             }
 
             Schedule::Yearly { day, time, month } => {
                 format!(
-                    "/mnt/data/recurring/yearly/{}/{}/{}/{}",
+                    "{}/recurring/yearly/{}/{}/{}/{}",
+                    data_root(),
                     &month.number_from_month(),
                     &day,
                     &time,
-                    &u_id
+                    &name
+                )
+            }
+
+            Schedule::MonthlyLastDay { time } => {
+                format!(
+                    "{}/recurring/monthly/last-day/{}/{}",
+                    data_root(),
+                    &time,
+                    &name
+                )
+            }
+
+            Schedule::MonthlyNthWeekday { nth, weekday, time } => {
+                format!(
+                    "{}/recurring/monthly/{}-{}/{}/{}",
+                    data_root(),
+                    &get_nth_display(nth),
+                    &get_weekday_display(weekday),
+                    &time,
+                    &name
+                )
+            }
+
+            Schedule::MonthlyLastBusinessDay { time } => {
+                format!(
+                    "{}/recurring/monthly/last-business-day/{}/{}",
+                    data_root(),
+                    &time,
+                    &name
                 )
             }
         }
     }
 }
 
-pub fn create_schedule(user_id: &String, configuration: Config, schedule: Schedule) -> bool {
+/// Saves the job and indexes it under its owner and under each target chat,
+/// so admins of those chats can manage it too. Returns false, with nothing
+/// left behind, when it couldn't be saved.
+pub fn create_schedule(user_id: &String, mut configuration: Config, schedule: Schedule) -> bool {
+    // nothing before now can have been missed
+    configuration.caught_up_at = Some(Utc::now());
+
+    let mut chats = configuration
+        .targets
+        .iter()
+        .map(|target| target.chat_id.to_owned())
+        .collect::<Vec<String>>();
+    // a chat may be targeted in several of its topics
+    chats.sort();
+    chats.dedup();
+
     match create_schedule_main(user_id, configuration, schedule) {
-        Ok(path) => create_schedule_index(user_id, &path),
+        Ok(path) => {
+            let mut indexed =
+                create_schedule_index(&format!("{}/users/{}", data_root(), user_id), &path);
+
+            for chat_id in chats {
+                indexed = create_schedule_index(&get_chat_directory(&chat_id), &path) && indexed;
+            }
+
+            // a job nobody can find would still be sent
+            if !indexed {
+                delete_job(&path);
+            }
+
+            indexed
+        }
         Err(()) => false,
     }
 }
 
-fn create_schedule_index(user_id: &String, path_str: &String) -> bool {
+thread_local! {
+    // everything is stored below it, tests point it at a temporary directory
+    static DATA_ROOT: RefCell<String> = RefCell::new(String::from("/mnt/data"));
+}
+
+fn data_root() -> String {
+    DATA_ROOT.with(|root| root.borrow().to_owned())
+}
+
+/// Stores everything of the current thread below `root` instead, for tests.
+pub fn set_data_root(root: &str) {
+    DATA_ROOT.with(|data| *data.borrow_mut() = root.to_owned());
+}
+
+pub fn new_job_id() -> String {
+    Ulid::new().to_string().to_lowercase()
+}
+
+fn get_chat_directory(chat_id: &String) -> String {
+    format!("{}/chats/{}", data_root(), chat_id)
+}
+
+fn create_schedule_index(sym_directory: &String, path_str: &String) -> bool {
     let path = canonicalize(Path::new(path_str)).unwrap();
-    let sym_directory = format!("users/{}", user_id);
-    let sym_path_directory = Path::new(&sym_directory);
+    let sym_path_directory = Path::new(sym_directory);
+
+    // held until the link exists, so concurrent requests can't pick the same number
+    let _lock = match lock_directory(sym_directory) {
+        Ok(lock) => lock,
+        Err(_) => return false,
+    };
 
     let mut i = 1;
 
     if sym_path_directory.exists() {
-        let files = sym_path_directory
+        // numbers of deleted jobs are not handed out again while later ones exist
+        i += sym_path_directory
             .read_dir()
             .unwrap()
-            .into_iter()
-            .map(|i| i.unwrap().file_name().to_str().unwrap().to_owned())
-            .collect::<Vec<String>>();
-
-        loop {
-            if files.contains(&i.to_string()) {
-                i += 1;
-            } else {
-                break;
-            }
-        }
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<i32>().ok())
+            .max()
+            .unwrap_or(0);
     } else {
         _ = create_dir_all(sym_path_directory);
     }
 
-    let sym = format!("users/{}/{}", user_id, i.to_string());
+    let sym = format!("{}/{}", sym_directory, i);
     let sym_path = Path::new(&sym);
 
     match symlink(path, sym_path) {
@@ -145,37 +958,125 @@ fn create_schedule_index(user_id: &String, path_str: &String) -> bool {
     }
 }
 
-fn create_schedule_main(
-    user_id: &String,
-    configuration: Config,
-    schedule: Schedule,
-) -> Result<String, ()> {
-    let file_content = serde_yaml::to_string(&configuration).unwrap();
+/// Locks an index directory against concurrent numbering. The lock file sits
+/// next to the directory and is released when the returned file is dropped.
+fn lock_directory(directory: &str) -> Result<File, String> {
+    lock_file(&format!("{}.lock", directory.trim_end_matches('/')))
+}
 
-    let file_name = schedule.get_file_location(user_id);
+/// Locks the job at `path` against concurrent updates until the returned file
+/// is dropped. Its lock file is named after the real path, in a directory of
+/// its own so it's never taken for a job.
+fn lock_job(path: &String) -> Result<File, String> {
+    let real_path = canonicalize(path).map_err(|e| format!("Could not find {}: {}", path, e))?;
+    let name = real_path
+        .to_string_lossy()
+        .trim_start_matches('/')
+        .replace('/', "+");
 
-    let path = Path::new(&file_name);
+    lock_file(&format!("{}/locks/{}", data_root(), name))
+}
 
-    create_dir_all(&path.parent().unwrap()).unwrap();
+fn lock_file(lock_path: &String) -> Result<File, String> {
+    if let Some(parent) = Path::new(&lock_path).parent() {
+        _ = create_dir_all(parent);
+    }
 
-    let mut file = File::create(path).unwrap();
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)
+        .map_err(|e| format!("Could not open {}: {}", lock_path, e))?;
+    file.lock_exclusive()
+        .map_err(|e| format!("Could not lock {}: {}", lock_path, e))?;
 
-    match file.write_all(file_content.as_bytes()) {
-        Ok(_) => Ok(file_name),
-        Err(_) => Err(()),
-    }
+    Ok(file)
 }
 
-pub fn delete_scheduled(user_id: &String, number: i32) {
-    let sym_file_path = format!("/mnt/data/users/{}/{}", user_id, number);
+/// Writes `content` to a hidden temp file next to `path`, syncs it and renames
+/// it into place, so readers see either the old or the new file, never half of one.
+fn write_atomic(path: &String, content: &str) -> Result<(), String> {
+    write_through_temp(path, content, |temp, target| rename(temp, target))
+}
 
-    let sym_path = Path::new(&sym_file_path);
+/// Like write_atomic, but fails when `path` already exists instead of
+/// replacing it. Linking, unlike renaming, doesn't overwrite.
+fn write_new(path: &String, content: &str) -> Result<(), String> {
+    write_through_temp(path, content, |temp, target| {
+        hard_link(temp, target)?;
+        remove_file(temp)
+    })
+}
+
+fn write_through_temp<F: Fn(&Path, &Path) -> std::io::Result<()>>(
+    path: &String,
+    content: &str,
+    place: F,
+) -> Result<(), String> {
+    let target = Path::new(path);
+    let directory = target.parent().unwrap_or(Path::new("."));
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // unique, so concurrent writers don't share a temp file
+    let temp = directory.join(format!(".{}.{}.tmp", name, new_job_id()));
+
+    let write = || -> std::io::Result<()> {
+        let mut file = File::create(&temp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        place(&temp, target)?;
+        // make the rename itself durable
+        File::open(directory)?.sync_all()
+    };
+
+    write().map_err(|e| {
+        _ = remove_file(&temp);
+        format!("Could not write {}: {}", path, e)
+    })
+}
+
+fn is_temp_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+fn create_schedule_main(
+    user_id: &String,
+    mut configuration: Config,
+    schedule: Schedule,
+) -> Result<String, ()> {
+    // named by id, a user may have any number of jobs in the same slot
+    let id = configuration.id.get_or_insert_with(new_job_id).to_owned();
+    configuration.owner.get_or_insert(user_id.to_owned());
+
+    let file_content = serde_yaml::to_string(&configuration).unwrap();
+
+    let file_name = schedule.get_file_location(&id);
+
+    let path = Path::new(&file_name);
+
+    create_dir_all(&path.parent().unwrap()).unwrap();
+
+    match write_new(&file_name, &file_content) {
+        Ok(_) => Ok(file_name),
+        Err(_) => Err(()),
+    }
+}
+
+pub fn delete_scheduled(user_id: &String, number: i32) {
+    let sym_file_path = format!("{}/users/{}/{}", data_root(), user_id, number);
+
+    let sym_path = Path::new(&sym_file_path);
 
     println!("{}", sym_file_path);
 
     match read_link(sym_path) {
         Ok(path) => {
-            _ = remove_file(path);
+            delete_job(&path.to_string_lossy().to_string());
         }
         Err(_) => {}
     };
@@ -183,8 +1084,212 @@ pub fn delete_scheduled(user_id: &String, number: i32) {
     _ = remove_file(sym_path);
 }
 
+/// Removes the schedule file at `path` and every index entry pointing at it.
+pub fn delete_job(path: &String) {
+    if let Ok(config) = load_config(path) {
+        unlink_job(path, &config);
+    }
+
+    _ = remove_file(path);
+}
+
+// drops the job from its owner's and its chats' indexes
+fn unlink_job(path: &String, config: &Config) {
+    let real_path = canonicalize(path).unwrap_or(Path::new(path).to_path_buf());
+
+    let mut directories = vec![format!("{}/users/{}", data_root(), get_owner(path, config))];
+    directories.extend(
+        config
+            .targets
+            .iter()
+            .map(|target| get_chat_directory(&target.chat_id)),
+    );
+
+    for directory in directories {
+        remove_links_to(Path::new(&directory), &real_path);
+    }
+}
+
+/// Moves a job that has ended out of the schedule into the archive directory,
+/// where it's named by its id. Returns its config and why it ended, None if
+/// it hasn't.
+pub fn archive_if_ended(
+    path: &String,
+    now: &DateTime<Utc>,
+) -> Result<Option<(Config, String)>, String> {
+    let config = load_config(path)?;
+    let reason = config
+        .get_end_reason(config.local_time(now).date())
+        .or_else(|| {
+            // e.g. the last day before the end date was skipped
+            let schedule = Schedule::from_file_location(path)?;
+            match config.next_occurrences(&schedule, now, 1).is_empty() {
+                true => Some("it has no more days to be sent on".to_owned()),
+                false => None,
+            }
+        });
+    let reason = match reason {
+        Some(reason) => reason,
+        None => return Ok(None),
+    };
+
+    // older jobs are named after their owner, who may have archived others
+    let config = ensure_id(path)?;
+    let id = config.id.to_owned().unwrap_or_default();
+    let real_path = canonicalize(path)
+        .map_err(|e| format!("Could not find {}: {}", path, e))?
+        .to_string_lossy()
+        .to_string();
+
+    let archive = format!("{}/archive", data_root());
+    create_dir_all(&archive).map_err(|e| format!("Could not create {}: {}", archive, e))?;
+    rename(&real_path, format!("{}/{}", archive, id))
+        .map_err(|e| format!("Could not archive {}: {}", path, e))?;
+
+    // only once it's out of the schedule, so a failed move leaves it listed
+    unlink_job(&real_path, &config);
+
+    for target in config.targets.iter() {
+        append_audit(
+            &target.chat_id,
+            &format!("job {} ended, {}", config.short_id(), reason),
+        );
+    }
+
+    Ok(Some((config, reason)))
+}
+
+fn remove_links_to(directory: &Path, target: &Path) {
+    let entries = match directory.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        if let Ok(link) = read_link(entry.path()) {
+            if link == target {
+                _ = remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// The numbered jobs indexed under a chat, as (number, schedule file) pairs.
+pub fn get_chat_jobs(chat_id: &String) -> Vec<(String, String)> {
+    get_indexed_jobs(&get_chat_directory(chat_id))
+}
+
+/// The numbered jobs of a user, as (number, schedule file) pairs.
+pub fn get_user_jobs(user_id: &String) -> Vec<(String, String)> {
+    get_indexed_jobs(&format!("{}/users/{}", data_root(), user_id))
+}
+
+fn get_indexed_jobs(directory: &String) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+
+    if let Ok(entries) = Path::new(directory).read_dir() {
+        for entry in entries.flatten() {
+            if let Ok(path) = read_link(entry.path()) {
+                result.push((
+                    entry.file_name().to_string_lossy().to_string(),
+                    path.to_string_lossy().to_string(),
+                ));
+            }
+        }
+    }
+
+    result.sort_by_key(|(number, _)| number.parse::<i32>().unwrap_or(i32::MAX));
+    result
+}
+
+/// Whether any job, scheduled or archived, already has the id `id`.
+pub fn is_job_id_taken(id: &str) -> bool {
+    Path::new(&format!("{}/archive/{}", data_root(), id)).exists()
+        || get_all_files()
+            .iter()
+            .any(|path| load_config(path).is_ok_and(|config| config.id.as_deref() == Some(id)))
+}
+
+/// Finds a job of a chat by its number or id, as (number, schedule file).
+pub fn get_chat_job(chat_id: &String, reference: &str) -> Result<(String, String), String> {
+    find_job(&get_chat_directory(chat_id), reference)
+        .ok_or(format!("There is no job {} for this chat.", reference))
+}
+
+/// Finds a job of a user by its number or id, as (number, schedule file).
+pub fn get_user_job(user_id: &String, reference: &str) -> Result<(String, String), String> {
+    find_job(&format!("{}/users/{}", data_root(), user_id), reference)
+        .ok_or(format!("You have no job {}.", reference))
+}
+
+fn find_job(directory: &String, reference: &str) -> Option<(String, String)> {
+    let reference = reference.trim_start_matches('#');
+
+    if reference.parse::<i32>().is_ok() {
+        let path = read_link(Path::new(&format!("{}/{}", directory, reference))).ok()?;
+        return Some((reference.to_owned(), path.to_string_lossy().to_string()));
+    }
+
+    for entry in Path::new(directory).read_dir().ok()?.flatten() {
+        if let Ok(path) = read_link(entry.path()) {
+            let path = path.to_string_lossy().to_string();
+
+            if load_config(&path).is_ok_and(|config| config.has_id(reference)) {
+                return Some((entry.file_name().to_string_lossy().to_string(), path));
+            }
+        }
+    }
+
+    None
+}
+
+/// Gives a job created before ids existed its id, returning the job's config.
+pub fn ensure_id(path: &String) -> Result<Config, String> {
+    let config = load_config(path)?;
+    if config.id.is_some() {
+        return Ok(config);
+    }
+
+    update_config(path, |config| {
+        config.id.get_or_insert_with(new_job_id);
+        Ok(())
+    })
+    .map(|(config, ())| config)
+}
+
+/// Records who changed what on the jobs of a chat.
+pub fn append_audit(chat_id: &String, entry: &String) {
+    _ = create_dir_all(format!("{}/audit", data_root()));
+
+    let file = OpenOptions::new().create(true).append(true).open(format!(
+        "{}/audit/{}",
+        data_root(),
+        chat_id
+    ));
+
+    if let Ok(mut file) = file {
+        _ = writeln!(
+            file,
+            "{} {}",
+            Utc::now().format("%Y-%m-%d %H:%M UTC"),
+            entry
+        );
+    }
+}
+
+/// The last `count` audit entries of a chat, oldest first.
+pub fn read_audit(chat_id: &String, count: usize) -> Vec<String> {
+    let data = read_to_string(format!("{}/audit/{}", data_root(), chat_id)).unwrap_or_default();
+    let lines = data
+        .lines()
+        .map(|line| line.to_owned())
+        .collect::<Vec<String>>();
+
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
 pub fn get_config(user_id: &String, number: i32) -> Result<Config, ()> {
-    let sym_file_path = format!("/mnt/data/users/{}/{}", user_id, number);
+    let sym_file_path = format!("{}/users/{}/{}", data_root(), user_id, number);
 
     let sym_path = Path::new(&sym_file_path);
 
@@ -197,6 +1302,712 @@ pub fn get_config(user_id: &String, number: i32) -> Result<Config, ()> {
     }
 }
 
+pub fn load_config(path: &String) -> Result<Config, String> {
+    let data = read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+
+    serde_yaml::from_str(&data).map_err(|e| format!("Could not parse {}: {}", path, e))
+}
+
+pub fn save_config(path: &String, config: &Config) -> Result<(), String> {
+    let content = serde_yaml::to_string(config).unwrap();
+
+    write_atomic(path, &content)
+}
+
+/// Loads the config at `path`, lets `update` change it and saves it again.
+/// The job is locked meanwhile so concurrent updates aren't lost, and nothing
+/// is saved when `update` fails.
+pub fn update_config<T, F: FnOnce(&mut Config) -> Result<T, String>>(
+    path: &String,
+    update: F,
+) -> Result<(Config, T), String> {
+    let _lock = lock_job(path)?;
+    let mut config = load_config(path)?;
+
+    let result = update(&mut config)?;
+    save_config(path, &config)?;
+
+    Ok((config, result))
+}
+
+/// Loads the config stored at `path`, selects the message to deliver and
+/// writes the advanced selection state back.
+pub fn take_message(path: &String) -> Result<(Config, Message), String> {
+    update_config(path, |config| {
+        if let Some(reason) = &config.disabled {
+            return Err(format!("{} is disabled: {}", path, reason));
+        }
+
+        let today = config.local_time(&Utc::now()).date();
+        if let Some(start) = config.start_date {
+            if today < start {
+                return Err(format!("{} starts on {}", path, start));
+            }
+        }
+        if let Some(reason) = config.get_end_reason(today) {
+            return Err(format!("{} has ended, {}", path, reason));
+        }
+        if config.is_skipped(today) {
+            return Err(format!("{} is skipped on {}", path, today));
+        }
+
+        let message = config
+            .next_message()
+            .ok_or(format!("{} has no messages", path))?;
+        config.occurrence_count += 1;
+        config.last_fired_at = Some(Utc::now());
+
+        Ok(message)
+    })
+}
+
+/// Looks for deliveries of the job at `path` missed before `now` and marks
+/// them as dealt with. The caller sends `send` of them late.
+pub fn take_missed(path: &String, now: &DateTime<Utc>) -> Option<Missed> {
+    let schedule = Schedule::from_file_location(path)?;
+
+    let (config, (due, send)) = update_config(path, |config| {
+        let due = match config.disabled {
+            Some(_) => Vec::new(),
+            None => config.missed_occurrences(&schedule, now),
+        };
+        if due.is_empty() {
+            return Err(format!("{} missed nothing", path));
+        }
+
+        let grace = config.grace_minutes.unwrap_or(DEFAULT_GRACE_MINUTES);
+        let in_grace = due
+            .iter()
+            .filter(|due| **due >= *now - chrono::Duration::minutes(grace as i64))
+            .count();
+        let send = match config.catch_up {
+            CatchUp::Once => in_grace.min(1),
+            CatchUp::All => in_grace,
+            CatchUp::Skip => 0,
+        };
+
+        config.caught_up_at = Some(*now);
+        Ok((due, send))
+    })
+    .ok()?;
+
+    for target in config.targets.iter() {
+        append_audit(
+            &target.chat_id,
+            &format!(
+                "job {} missed {} deliveries, {} sent late",
+                config.short_id(),
+                due.len(),
+                send
+            ),
+        );
+    }
+
+    Some(Missed { config, due, send })
+}
+
+/// Records that the dispatcher ran at `now`, returning when it ran before.
+pub fn mark_dispatched(now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let path = &format!("{}/dispatched", data_root());
+    let previous = read_to_string(path)
+        .ok()
+        .and_then(|text| DateTime::parse_from_rfc3339(text.trim()).ok())
+        .map(|previous| previous.with_timezone(&Utc));
+
+    if let Err(e) = std::fs::write(path, now.to_rfc3339()) {
+        println!("could not write {}: {}", path, e);
+    }
+
+    previous
+}
+
+/// Owner of the job at `path`. Older files don't record it, but are named after it.
+pub fn get_owner(path: &String, config: &Config) -> String {
+    match &config.owner {
+        Some(owner) => owner.to_owned(),
+        None => Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Repairs the damage of the old layout, where a user's second job in a slot
+/// overwrote the first: index entries sharing a file are reduced to one, and
+/// files named after their owner are renamed to their job id. Returns what was done.
+pub fn migrate_collisions() -> Vec<String> {
+    let mut report = Vec::new();
+    let directories = get_index_directories();
+
+    for directory in directories.iter() {
+        for (number, target) in get_duplicate_links(directory) {
+            _ = remove_file(directory.join(number.to_string()));
+            report.push(format!(
+                "removed {}/{}, its job was overwritten by another at {}",
+                directory.display(),
+                number,
+                target.display()
+            ));
+        }
+    }
+
+    for path in get_all_files() {
+        let mut config = match load_config(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                report.push(e);
+                continue;
+            }
+        };
+
+        let name = Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        if config.id.is_some() && config.id == name {
+            continue;
+        }
+
+        let old_path = canonicalize(&path).unwrap_or(Path::new(&path).to_path_buf());
+        let id = config.id.get_or_insert_with(new_job_id).to_owned();
+        config.owner = Some(get_owner(&path, &config));
+
+        let new_path = Path::new(&path).with_file_name(&id);
+        let new_name = new_path.to_string_lossy().to_string();
+        if new_path.exists() {
+            report.push(format!("skipped {}, {} already exists", path, new_name));
+            continue;
+        }
+
+        if let Err(e) = save_config(&path, &config) {
+            report.push(e);
+            continue;
+        }
+        if let Err(e) = rename(&path, &new_path) {
+            report.push(format!("could not move {}: {}", path, e));
+            continue;
+        }
+
+        let new_target = canonicalize(&new_path).unwrap_or(new_path.to_owned());
+        for directory in directories.iter() {
+            relink(directory, &old_path, &new_target);
+        }
+
+        report.push(format!("moved {} to {}", path, new_name));
+    }
+
+    report
+}
+
+/// Moves jobs from the old biweekly/{odd}/{weekday} layout, which went by the
+/// parity of the ISO week, to two week intervals. The anchor is their next
+/// date from `today`, so jobs keep their current rhythm.
+pub fn migrate_biweekly(today: NaiveDate) -> Vec<String> {
+    let mut report = Vec::new();
+    let directories = get_index_directories();
+
+    let mut files = Vec::new();
+    collect_files(
+        Path::new(&format!("{}/recurring/biweekly", data_root())),
+        &mut files,
+    );
+
+    for path in files {
+        // .../biweekly/{odd}/{weekday}/{time}/{name}
+        let parts = path.rsplit('/').take(5).collect::<Vec<&str>>();
+        // despite the name, "1" held the jobs of even ISO weeks
+        let (name, time, weekday, even_week) = match parts[..] {
+            [name, time, weekday, odd, "biweekly"] => (name, time, weekday, odd == "1"),
+            _ => {
+                report.push(format!("skipped {}, not a biweekly job", path));
+                continue;
+            }
+        };
+        if is_temp_file(Path::new(&path)) {
+            continue;
+        }
+
+        let anchor = weekday.parse::<Weekday>().ok().and_then(|weekday| {
+            (0..14)
+                .filter_map(|i| today.checked_add_days(Days::new(i)))
+                .find(|date| {
+                    date.weekday() == weekday
+                        && date.iso_week().week().is_multiple_of(2) == even_week
+                })
+        });
+        let anchor = match anchor {
+            Some(anchor) => anchor,
+            None => {
+                report.push(format!("skipped {}, unknown weekday {}", path, weekday));
+                continue;
+            }
+        };
+
+        let schedule = Schedule::Interval {
+            every: 2,
+            unit: IntervalUnit::Weeks,
+            anchor,
+            time: time.to_owned(),
+        };
+        let new_name = schedule.get_file_location(&name.to_owned());
+        let new_path = Path::new(&new_name);
+        if new_path.exists() {
+            report.push(format!("skipped {}, {} already exists", path, new_name));
+            continue;
+        }
+
+        let old_path = canonicalize(&path).unwrap_or(Path::new(&path).to_path_buf());
+        _ = create_dir_all(new_path.parent().unwrap());
+        if let Err(e) = rename(&path, new_path) {
+            report.push(format!("could not move {}: {}", path, e));
+            continue;
+        }
+
+        let new_target = canonicalize(new_path).unwrap_or(new_path.to_path_buf());
+        for directory in directories.iter() {
+            relink(directory, &old_path, &new_target);
+        }
+
+        report.push(format!("moved {} to {}", path, new_name));
+    }
+
+    report
+}
+
+/// The users/{id} and chats/{id} directories holding numbered links to jobs.
+fn get_index_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+
+    for root in ["users", "chats"].map(|index| format!("{}/{}", data_root(), index)) {
+        if let Ok(entries) = Path::new(&root).read_dir() {
+            directories.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir()),
+            );
+        }
+    }
+
+    directories
+}
+
+/// Numbered links pointing at the same file as a lower number in `directory`.
+fn get_duplicate_links(directory: &Path) -> Vec<(i32, PathBuf)> {
+    let mut links = match directory.read_dir() {
+        Ok(entries) => entries
+            .flatten()
+            .filter_map(|entry| {
+                let number = entry.file_name().to_str()?.parse::<i32>().ok()?;
+                Some((number, read_link(entry.path()).ok()?))
+            })
+            .collect::<Vec<(i32, PathBuf)>>(),
+        Err(_) => return Vec::new(),
+    };
+    links.sort();
+
+    let mut seen = Vec::new();
+    let mut duplicates = Vec::new();
+    for (number, target) in links {
+        if seen.contains(&target) {
+            duplicates.push((number, target));
+        } else {
+            seen.push(target);
+        }
+    }
+
+    duplicates
+}
+
+/// Checks the store for dangling index links, duplicate indices, schedule files
+/// their owner can't reach and leftover temp files. With `repair` set, each
+/// problem is fixed as well. Returns one line per problem found.
+pub fn fsck(repair: bool) -> Vec<String> {
+    let mut report = Vec::new();
+    let directories = get_index_directories();
+
+    for directory in directories.iter() {
+        let _lock = match repair {
+            true => lock_directory(&directory.to_string_lossy()).ok(),
+            false => None,
+        };
+
+        if let Ok(entries) = directory.read_dir() {
+            for entry in entries.flatten() {
+                let link = match read_link(entry.path()) {
+                    Ok(link) => link,
+                    Err(_) => continue,
+                };
+
+                if !link.exists() {
+                    report.push(format!(
+                        "dangling {} -> {}",
+                        entry.path().display(),
+                        link.display()
+                    ));
+                    if repair {
+                        _ = remove_file(entry.path());
+                    }
+                }
+            }
+        }
+
+        for (number, target) in get_duplicate_links(directory) {
+            report.push(format!(
+                "duplicate {}/{} -> {}",
+                directory.display(),
+                number,
+                target.display()
+            ));
+            if repair {
+                _ = remove_file(directory.join(number.to_string()));
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    collect_files(Path::new(&format!("{}/recurring", data_root())), &mut files);
+
+    for path in files {
+        if is_temp_file(Path::new(&path)) {
+            report.push(format!("leftover temp file {}", path));
+            if repair {
+                _ = remove_file(&path);
+            }
+            continue;
+        }
+
+        let config = match load_config(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                report.push(e);
+                continue;
+            }
+        };
+
+        let owner_directory = format!("{}/users/{}", data_root(), get_owner(&path, &config));
+        let target = canonicalize(&path).unwrap_or(Path::new(&path).to_path_buf());
+        let indexed = Path::new(&owner_directory)
+            .read_dir()
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .any(|entry| read_link(entry.path()).is_ok_and(|link| link == target))
+            })
+            .unwrap_or(false);
+
+        if !indexed {
+            report.push(format!(
+                "orphaned {}, not listed in {}",
+                path, owner_directory
+            ));
+            if repair {
+                create_schedule_index(&owner_directory, &path);
+            }
+        }
+    }
+
+    report
+}
+
+fn relink(directory: &Path, from: &Path, to: &Path) {
+    let entries = match directory.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        if read_link(entry.path()).is_ok_and(|link| link == from) {
+            _ = remove_file(entry.path());
+            _ = symlink(to, entry.path());
+        }
+    }
+}
+
+/// Every schedule file under the recurring tree.
+pub fn get_all_files() -> Vec<String> {
+    let mut result = Vec::new();
+    collect_files(
+        Path::new(&format!("{}/recurring", data_root())),
+        &mut result,
+    );
+    result.retain(|path| !is_temp_file(Path::new(path)));
+
+    result
+}
+
+fn collect_files(directory: &Path, result: &mut Vec<String>) {
+    let entries = match directory.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_files(&path, result);
+        } else {
+            result.push(path.to_string_lossy().to_string());
+        }
+    }
+}
+
+/// The times of day of a slot's time directory: "09:00", a list like
+/// "08:00,12:00,18:00" or a range like "every-2h-08:00-18:00".
+pub fn parse_times(spec: &str) -> Option<Vec<NaiveTime>> {
+    let parse = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").ok();
+
+    if let Some(range) = spec.strip_prefix("every-") {
+        let mut parts = range.splitn(3, '-');
+        let hours = parts.next()?.strip_suffix('h')?.parse::<u32>().ok()?;
+        let from = parse(parts.next()?)?;
+        let to = parse(parts.next()?)?;
+        if hours == 0 || to < from {
+            return None;
+        }
+
+        // in minutes from midnight
+        let from = from.hour() * 60 + from.minute();
+        let to = to.hour() * 60 + to.minute();
+
+        return (from..=to)
+            .step_by(hours as usize * 60)
+            .map(|minute| NaiveTime::from_hms_opt(minute / 60, minute % 60, 0))
+            .collect();
+    }
+
+    spec.split(',').map(parse).collect()
+}
+
+/// A list spec for `times`, e.g. "08:00,12:00,18:00".
+pub fn format_times(times: &[NaiveTime]) -> String {
+    let mut times = times.to_vec();
+    times.sort();
+    times.dedup();
+
+    times
+        .iter()
+        .map(|time| time.format("%H:%M").to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// A range spec, sent every `hours` from `from` up to `to`.
+pub fn format_time_range(hours: u32, from: NaiveTime, to: NaiveTime) -> String {
+    format!(
+        "every-{}h-{}-{}",
+        hours,
+        from.format("%H:%M"),
+        to.format("%H:%M")
+    )
+}
+
+/// e.g. "at 09:00", "at 08:00, 12:00 and 18:00" or "every 2 hours from 08:00 to 18:00"
+pub fn get_times_display(spec: &str) -> String {
+    if let Some(range) = spec.strip_prefix("every-") {
+        let parts = range.splitn(3, '-').collect::<Vec<&str>>();
+        if let [hours, from, to] = parts[..] {
+            return match hours.trim_end_matches('h') {
+                "1" => format!("every hour from {} to {}", from, to),
+                hours => format!("every {} hours from {} to {}", hours, from, to),
+            };
+        }
+    }
+
+    let times = spec.split(',').collect::<Vec<&str>>();
+    match times.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("at {} and {}", rest.join(", "), last)
+        }
+        _ => format!("at {}", spec),
+    }
+}
+
+/// Returns the schedule files that are due in the minute of `now`.
+pub fn get_due_files(now: &NaiveDateTime) -> Vec<String> {
+    let date = now.date();
+    let weekday = get_weekday_display(date.weekday());
+    let odd = if date.iso_week().week().is_multiple_of(2) {
+        "1"
+    } else {
+        "0"
+    };
+
+    // (slot, policy a job needs to be due when its day is missing this month)
+    let mut slots = vec![
+        (format!("{}/recurring/daily", data_root()), None),
+        (
+            format!("{}/recurring/weekly/{}", data_root(), weekday),
+            None,
+        ),
+        // older biweekly jobs, until `dispatcher migrate` has moved them
+        (
+            format!("{}/recurring/biweekly/{}/{}", data_root(), odd, weekday),
+            None,
+        ),
+        (
+            format!("{}/recurring/monthly/{}", data_root(), date.day()),
+            None,
+        ),
+        (
+            format!(
+                "{}/recurring/yearly/{}/{}",
+                data_root(),
+                date.month(),
+                date.day()
+            ),
+            None,
+        ),
+    ];
+    slots.extend(get_missing_day_slots(date));
+    slots.extend(get_interval_slots(date));
+    slots.extend(
+        get_monthly_rule_slots(date)
+            .into_iter()
+            .map(|slot| (slot, None)),
+    );
+
+    let mut result = Vec::new();
+
+    for (slot, policy) in slots {
+        let entries = match Path::new(&slot).read_dir() {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // times are stored as typed, so "9:05" and "09:05" are the same slot
+            let due = parse_times(&name).is_some_and(|times| {
+                times
+                    .iter()
+                    .any(|time| time.format("%H:%M").to_string() == now.format("%H:%M").to_string())
+            });
+
+            if !due {
+                continue;
+            }
+
+            if let Ok(files) = entry.path().read_dir() {
+                for file in files.flatten() {
+                    if is_temp_file(&file.path()) {
+                        continue;
+                    }
+
+                    let path = file.path().to_string_lossy().to_string();
+                    let wanted = match policy {
+                        Some(policy) => load_config(&path)
+                            .map(|config| config.missing_day == policy)
+                            .unwrap_or(false),
+                        None => true,
+                    };
+
+                    if wanted {
+                        result.push(path);
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// interval/{unit}/{every}/{anchor} directories with jobs due on `date`
+fn get_interval_slots(date: NaiveDate) -> Vec<(String, Option<MissingDay>)> {
+    let mut slots = Vec::new();
+
+    for unit in [
+        IntervalUnit::Days,
+        IntervalUnit::Weeks,
+        IntervalUnit::Months,
+    ] {
+        let unit_directory = format!("{}/recurring/interval/{}", data_root(), unit);
+        let intervals = match Path::new(&unit_directory).read_dir() {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for interval in intervals.flatten() {
+            let every = match interval.file_name().to_string_lossy().parse::<u32>() {
+                Ok(every) => every,
+                Err(_) => continue,
+            };
+            let anchors = match interval.path().read_dir() {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for anchor in anchors.flatten() {
+                let name = anchor.file_name().to_string_lossy().to_string();
+                let due = NaiveDate::parse_from_str(&name, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|start| is_interval_due(every, unit, start, date));
+
+                if let Some(policy) = due {
+                    slots.push((anchor.path().to_string_lossy().to_string(), policy));
+                }
+            }
+        }
+    }
+
+    slots
+}
+
+// slots of the monthly rules that fall on `date`, e.g. "second-tuesday"
+fn get_monthly_rule_slots(date: NaiveDate) -> Vec<String> {
+    let (year, month) = (date.year(), date.month());
+    let weekday = date.weekday();
+    let mut rules = Vec::new();
+
+    if get_last_day_of_month(year, month) == Some(date) {
+        rules.push("last-day".to_owned());
+    }
+    if get_last_business_day(year, month) == Some(date) {
+        rules.push("last-business-day".to_owned());
+    }
+
+    for nth in [1, 2, 3, 4, -1] {
+        if get_nth_weekday(year, month, nth, weekday) == Some(date) {
+            rules.push(format!(
+                "{}-{}",
+                get_nth_display(nth),
+                get_weekday_display(weekday)
+            ));
+        }
+    }
+
+    rules
+        .iter()
+        .map(|rule| format!("{}/recurring/monthly/{}", data_root(), rule))
+        .collect()
+}
+
+// days the month doesn't have are sent on its last day or on the 1st of the
+// next month, for jobs asking for that
+fn get_missing_day_slots(date: NaiveDate) -> Vec<(String, Option<MissingDay>)> {
+    let mut slots = Vec::new();
+
+    let (month, policy) = match (date.succ_opt(), date.pred_opt()) {
+        (Some(next), _) if next.month() != date.month() => (date, MissingDay::LastDay),
+        (_, Some(previous)) if date.day() == 1 => (previous, MissingDay::NextDay),
+        _ => return slots,
+    };
+
+    for day in (month.day() + 1)..=31 {
+        slots.push((
+            format!("{}/recurring/monthly/{}", data_root(), day),
+            Some(policy),
+        ));
+        slots.push((
+            format!("{}/recurring/yearly/{}/{}", data_root(), month.month(), day),
+            Some(policy),
+        ));
+    }
+
+    slots
+}
+
 // pub fn list_user_jobs(user_id: &String) -> Vec<Config> {
 
 // }
@@ -204,13 +2015,643 @@ pub fn get_config(user_id: &String, number: i32) -> Result<Config, ()> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    // points the data directory of this test's thread at a new temporary one
+    fn use_temp_root() -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        set_data_root(&root.path().to_string_lossy());
+        root
+    }
+
+    fn time_strategy() -> impl Strategy<Value = String> {
+        prop_oneof![
+            (0u32..24, 0u32..60).prop_map(|(hour, minute)| format!("{:02}:{:02}", hour, minute)),
+            (1u32..6, 0u32..12, 12u32..24).prop_map(|(every, from, to)| format!(
+                "every-{}h-{:02}:30-{:02}:00",
+                every, from, to
+            )),
+        ]
+    }
+
+    fn schedule_strategy() -> impl Strategy<Value = Schedule> {
+        let weekday = (0u8..7).prop_map(|day| Weekday::try_from(day).unwrap());
+        let unit = prop_oneof![
+            Just(IntervalUnit::Days),
+            Just(IntervalUnit::Weeks),
+            Just(IntervalUnit::Months)
+        ];
+        let anchor = (0u64..3000)
+            .prop_map(|days| NaiveDate::from_ymd_opt(2020, 1, 1).unwrap() + Days::new(days));
+
+        prop_oneof![
+            time_strategy().prop_map(|time| Schedule::Daily { time }),
+            (weekday.clone(), time_strategy())
+                .prop_map(|(weekday, time)| Schedule::Weekly { weekday, time }),
+            (1u32..5, unit, anchor, time_strategy()).prop_map(|(every, unit, anchor, time)| {
+                Schedule::Interval {
+                    every,
+                    unit,
+                    anchor,
+                    time,
+                }
+            }),
+            (1i32..=31, time_strategy()).prop_map(|(day, time)| Schedule::Monthly { day, time }),
+            (1u8..=12, 1i32..=31, time_strategy()).prop_map(|(month, day, time)| {
+                Schedule::Yearly {
+                    day,
+                    time,
+                    month: Month::try_from(month).unwrap(),
+                }
+            }),
+            time_strategy().prop_map(|time| Schedule::MonthlyLastDay { time }),
+            (
+                prop::sample::select(vec![1, 2, 3, 4, -1]),
+                weekday,
+                time_strategy()
+            )
+                .prop_map(|(nth, weekday, time)| Schedule::MonthlyNthWeekday {
+                    nth,
+                    weekday,
+                    time
+                }),
+            time_strategy().prop_map(|time| Schedule::MonthlyLastBusinessDay { time }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_next_occurrences_properties(
+            schedule in schedule_strategy(),
+            seconds in 1_577_836_800i64..2_208_988_800,
+            n in 1usize..6,
+        ) {
+            let after = DateTime::from_timestamp(seconds, 0).unwrap();
+            let occurrences = schedule
+                .next_occurrences(&after, &Tz::UTC, n)
+                .iter()
+                .map(|occurrence| occurrence.with_timezone(&Utc))
+                .collect::<Vec<DateTime<Utc>>>();
+            let times = parse_times(schedule.get_time()).unwrap();
+
+            prop_assert!(occurrences.len() <= n);
+            prop_assert!(occurrences.windows(2).all(|pair| pair[0] < pair[1]));
+            for occurrence in occurrences.iter() {
+                prop_assert!(*occurrence > after);
+                prop_assert!(schedule.is_due_on(occurrence.date_naive(), MissingDay::Skip));
+                prop_assert!(times.contains(&occurrence.time()));
+            }
+
+            // nothing due is left out before the last one
+            if let Some(last) = occurrences.last() {
+                let dates = after.date_naive().iter_days();
+                for date in dates.take_while(|date| *date <= last.date_naive()) {
+                    if !schedule.is_due_on(date, MissingDay::Skip) {
+                        continue;
+                    }
+                    for time in times.iter() {
+                        let expected = date.and_time(*time).and_utc();
+                        if expected > after && expected <= *last {
+                            prop_assert!(occurrences.contains(&expected));
+                        }
+                    }
+                }
+            }
+
+            // asking again from one of them carries on with the rest
+            if occurrences.len() > 1 {
+                let rest = schedule
+                    .next_occurrences(&occurrences[0], &Tz::UTC, n - 1)
+                    .iter()
+                    .map(|occurrence| occurrence.with_timezone(&Utc))
+                    .collect::<Vec<DateTime<Utc>>>();
+                prop_assert_eq!(&rest[..], &occurrences[1..]);
+            }
+        }
+
+        #[test]
+        fn test_due_files_match_next_occurrences(
+            schedule in schedule_strategy(),
+            missing_day in prop_oneof![
+                Just(MissingDay::Skip),
+                Just(MissingDay::LastDay),
+                Just(MissingDay::NextDay)
+            ],
+            minutes in 26_297_280i64..36_816_480,
+            offsets in prop::collection::vec(1i64..60 * 24 * 70, 8),
+        ) {
+            let _root = use_temp_root();
+            let mut config = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+            config.missing_day = missing_day;
+            let path = schedule.clone().get_file_location(config.id.as_ref().unwrap());
+            create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+            save_config(&path, &config).unwrap();
+
+            let after = DateTime::from_timestamp(minutes * 60, 0).unwrap();
+            let occurrences = config
+                .next_occurrences(&schedule, &after, 3)
+                .iter()
+                .map(|occurrence| occurrence.with_timezone(&Utc))
+                .collect::<Vec<DateTime<Utc>>>();
+            let is_due = |minute: &DateTime<Utc>| get_due_files(&minute.naive_utc()).contains(&path);
+
+            // the dispatcher sends it at every predicted minute
+            for occurrence in occurrences.iter() {
+                prop_assert!(is_due(occurrence));
+            }
+
+            // and at no other minute before the last of them
+            let minutes = offsets
+                .iter()
+                .map(|offset| after + chrono::Duration::minutes(*offset))
+                .chain(occurrences.iter().map(|occurrence| *occurrence - chrono::Duration::minutes(1)));
+            for minute in minutes {
+                if occurrences.len() == 3 && minute > occurrences[2] {
+                    continue;
+                }
+                prop_assert_eq!(is_due(&minute), occurrences.contains(&minute));
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_next_occurrences() {
+        let after = NaiveDate::from_ymd_opt(2026, 10, 14)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+            .and_utc();
+        let schedule = Schedule::Monthly {
+            day: 31,
+            time: "09:00".to_owned(),
+        };
+        let show = |occurrences: Vec<DateTime<Tz>>| {
+            occurrences
+                .iter()
+                .map(|occurrence| occurrence.format("%a %-d %b %H:%M").to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            show(schedule.next_occurrences(&after, &Tz::Europe__Berlin, 2)),
+            ["Sat 31 Oct 10:00", "Thu 31 Dec 10:00"]
+        );
+
+        let mut config = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+        config.missing_day = MissingDay::LastDay;
+        config
+            .skip_dates
+            .push(NaiveDate::from_ymd_opt(2026, 12, 31).unwrap());
+        config.max_deliveries = Some(3);
+        config.occurrence_count = 1;
+        assert_eq!(
+            show(config.next_occurrences(&schedule, &after, 5)),
+            ["Sat 31 Oct 09:00", "Mon 30 Nov 09:00"]
+        );
+
+        // 23:30 UTC is already the next day in Berlin, where the skip applies
+        let schedule = Schedule::Daily {
+            time: "12:00,23:30".to_owned(),
+        };
+        let mut config = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+        config.timezone = Some("Europe/Berlin".to_owned());
+        config
+            .skip_dates
+            .push(NaiveDate::from_ymd_opt(2026, 10, 15).unwrap());
+        assert_eq!(
+            show(config.next_occurrences(&schedule, &after, 3)),
+            ["Wed 14 Oct 14:00", "Fri 16 Oct 01:30", "Fri 16 Oct 14:00"]
+        );
+    }
+
+    #[test]
+    pub fn test_missed_occurrences() {
+        let at = |d, h, m| {
+            NaiveDate::from_ymd_opt(2026, 10, d)
+                .unwrap()
+                .and_hms_opt(h, m, 30)
+                .unwrap()
+                .and_utc()
+        };
+        let schedule = Schedule::Daily {
+            time: "09:00,12:00".to_owned(),
+        };
+
+        let mut config = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+        assert!(config
+            .missed_occurrences(&schedule, &at(16, 10, 0))
+            .is_empty());
+
+        // down from the 14th at noon until the 16th, now is the minute being dispatched
+        config.last_fired_at = Some(at(14, 9, 0));
+        config.caught_up_at = Some(at(14, 10, 0));
+        config
+            .skip_dates
+            .push(NaiveDate::from_ymd_opt(2026, 10, 15).unwrap());
+        assert_eq!(
+            config.missed_occurrences(&schedule, &at(16, 12, 0)),
+            [at(14, 12, 0), at(16, 9, 0)]
+                .map(|due| due.with_second(0).unwrap())
+                .to_vec()
+        );
+    }
+
+    #[test]
+    pub fn test_take_missed() {
+        let _root = use_temp_root();
+        let schedule = Schedule::Daily {
+            time: "every-1h-00:00-23:00".to_owned(),
+        };
+        let mut config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("hourly".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.catch_up = CatchUp::All;
+        config.grace_minutes = Some(150);
+
+        let at = |day, hour| {
+            NaiveDate::from_ymd_opt(2026, 10, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+
+        // down for ten days, far more deliveries than are sent late
+        let now = at(20, 12) + chrono::Duration::seconds(30);
+        config.last_fired_at = Some(now - chrono::Duration::days(10));
+        let path = schedule.get_file_location(config.id.as_ref().unwrap());
+        create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        save_config(&path, &config).unwrap();
+
+        let missed = take_missed(&path, &now).unwrap();
+        assert_eq!(missed.due.len(), 239);
+        assert_eq!(missed.due[0], at(10, 13));
+        assert_eq!(missed.due[238], at(20, 11));
+        // 10:00 and 11:00 are within the grace window
+        assert_eq!(missed.send, 2);
+
+        assert_eq!(load_config(&path).unwrap().caught_up_at, Some(now));
+        assert!(take_missed(&path, &now).is_none());
+    }
+
+    #[test]
+    pub fn test_archive_if_ended() {
+        let _root = use_temp_root();
+        let mut config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("once".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.id = None;
+        config.max_deliveries = Some(1);
+        config.occurrence_count = 1;
+
+        // an older job, named after its owner
+        let schedule = Schedule::Daily {
+            time: "09:00".to_owned(),
+        };
+        let path = schedule.get_file_location(&"42".to_owned());
+        create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        save_config(&path, &config).unwrap();
+        let index = format!("{}/users/42", data_root());
+        assert!(create_schedule_index(&index, &path));
+
+        let (config, reason) = archive_if_ended(&path, &Utc::now()).unwrap().unwrap();
+        assert_eq!(reason, "it was sent 1 times");
+        assert!(!Path::new(&path).exists());
+        let archived = format!("{}/archive/{}", data_root(), config.id.unwrap());
+        assert!(load_config(&archived).is_ok());
+        assert_eq!(Path::new(&index).read_dir().unwrap().count(), 0);
+    }
+
+    #[test]
+    pub fn test_migrate_collisions() {
+        let _root = use_temp_root();
+        let schedule = Schedule::Daily {
+            time: "09:00".to_owned(),
+        };
+        let save = |name: &str, config: &Config| {
+            let path = schedule.clone().get_file_location(&name.to_owned());
+            create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+            save_config(&path, config).unwrap();
+            path
+        };
+        let new_config = || {
+            Config::new(
+                vec![Target::new("-100123".to_owned())],
+                vec![Message::Message("hi".to_owned())],
+                Selection::RoundRobin,
+            )
+        };
+
+        // user 42's second job in the slot overwrote the first, both links remain
+        let mut legacy = new_config();
+        legacy.id = None;
+        let legacy_path = save("42", &legacy);
+        let user = format!("{}/users/42", data_root());
+        let chat = format!("{}/chats/-100123", data_root());
+        for index in [&user, &user, &chat] {
+            assert!(create_schedule_index(index, &legacy_path));
+        }
+
+        // already named by its id
+        let current = new_config();
+        let current_path = save(current.id.as_ref().unwrap(), &current);
+        assert!(create_schedule_index(&user, &current_path));
+
+        let report = migrate_collisions();
+        assert_eq!(report.len(), 2, "{:?}", report);
+        assert!(report[0].starts_with(&format!("removed {}/2", user)));
+
+        let (_, migrated) = get_user_job(&"42".to_owned(), "1").unwrap();
+        let config = load_config(&migrated).unwrap();
+        assert_eq!(config.owner, Some("42".to_owned()));
+        assert!(migrated.ends_with(config.id.as_ref().unwrap()));
+        assert!(!Path::new(&legacy_path).exists());
+
+        // every index follows the move, the other job is left alone
+        assert!(!Path::new(&format!("{}/2", user)).exists());
+        assert_eq!(
+            PathBuf::from(get_user_job(&"42".to_owned(), "3").unwrap().1),
+            canonicalize(&current_path).unwrap()
+        );
+        assert_eq!(
+            read_link(format!("{}/1", chat)).unwrap(),
+            canonicalize(&migrated).unwrap()
+        );
+        assert!(migrate_collisions().is_empty());
+    }
+
+    #[test]
+    pub fn test_migrate_biweekly() {
+        let _root = use_temp_root();
+        let mut config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("every other monday".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.id = None;
+        let path = format!("{}/recurring/biweekly/1/monday/09:00/42", data_root());
+        create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        save_config(&path, &config).unwrap();
+        let user = format!("{}/users/42", data_root());
+        assert!(create_schedule_index(&user, &path));
+
+        // a thursday in ISO week 42, the next monday of an even week is the 26th
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let dispatched = |path: &String| {
+            today
+                .iter_days()
+                .take(28)
+                .map(|date| date.and_hms_opt(9, 0, 0).unwrap())
+                .filter(|now| get_due_files(now).contains(path))
+                .map(|now| now.date())
+                .collect::<Vec<NaiveDate>>()
+        };
+        let before = dispatched(&path);
+        assert_eq!(before[0], NaiveDate::from_ymd_opt(2026, 10, 26).unwrap());
+
+        let report = migrate_biweekly(today);
+        assert_eq!(report.len(), 1, "{:?}", report);
+        assert!(!Path::new(&path).exists());
+
+        let (_, migrated) = get_user_job(&"42".to_owned(), "1").unwrap();
+        assert_eq!(
+            Schedule::from_file_location(&migrated),
+            Some(Schedule::Interval {
+                every: 2,
+                unit: IntervalUnit::Weeks,
+                anchor: before[0],
+                time: "09:00".to_owned(),
+            })
+        );
+        // sent on the same days as before
+        assert_eq!(dispatched(&migrated), before);
+    }
+
+    #[test]
+    pub fn test_create_schedule_indexes_chats_once() {
+        let _root = use_temp_root();
+        let config = Config::new(
+            vec![
+                Target::in_thread("-100123".to_owned(), 2),
+                Target::new("@other".to_owned()),
+                Target::in_thread("-100123".to_owned(), 3),
+            ],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+        let schedule = Schedule::Daily {
+            time: "09:00".to_owned(),
+        };
+
+        assert!(create_schedule(&"42".to_owned(), config, schedule));
+        assert_eq!(get_chat_jobs(&"-100123".to_owned()).len(), 1);
+        assert_eq!(get_chat_jobs(&"@other".to_owned()).len(), 1);
+    }
+
+    #[test]
+    pub fn test_create_schedule_removes_unindexed_job() {
+        let root = use_temp_root();
+        // the chat indexes can't be written
+        File::create(root.path().join("chats")).unwrap();
+
+        let config = Config::new(
+            vec![Target::new("-100123".to_owned())],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+        let schedule = Schedule::Daily {
+            time: "09:00".to_owned(),
+        };
+
+        assert!(!create_schedule(&"42".to_owned(), config, schedule));
+        assert!(get_all_files().is_empty());
+        assert!(get_user_job(&"42".to_owned(), "1").is_err());
+    }
+
+    #[test]
+    pub fn test_missing_day() {
+        assert_eq!(MissingDay::Skip.resolve(2026, 4, 31), None);
+        assert_eq!(
+            MissingDay::LastDay.resolve(2028, 2, 31),
+            NaiveDate::from_ymd_opt(2028, 2, 29)
+        );
+        assert_eq!(
+            MissingDay::NextDay.resolve(2027, 2, 29),
+            NaiveDate::from_ymd_opt(2027, 3, 1)
+        );
+        assert_eq!(
+            MissingDay::Skip.resolve(2027, 3, 31),
+            NaiveDate::from_ymd_opt(2027, 3, 31)
+        );
+
+        // on april 30th the jobs for the 31st with "last day" are looked at
+        let slots = get_missing_day_slots(NaiveDate::from_ymd_opt(2026, 4, 30).unwrap());
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].0, "/mnt/data/recurring/monthly/31");
+        assert_eq!(slots[0].1, Some(MissingDay::LastDay));
+
+        // and on march 1st the ones for february 29th to 31st with "next day"
+        let slots = get_missing_day_slots(NaiveDate::from_ymd_opt(2027, 3, 1).unwrap());
+        assert_eq!(slots.len(), 6);
+        assert_eq!(slots[1].0, "/mnt/data/recurring/yearly/2/29");
+        assert_eq!(slots[1].1, Some(MissingDay::NextDay));
+
+        assert!(get_missing_day_slots(NaiveDate::from_ymd_opt(2027, 3, 31).unwrap()).is_empty());
+    }
+
+    #[test]
+    pub fn test_monthly_rules() {
+        // october 2026 starts on a thursday and ends on a saturday
+        assert_eq!(
+            get_nth_weekday(2026, 10, 2, Weekday::Tue),
+            NaiveDate::from_ymd_opt(2026, 10, 13)
+        );
+        assert_eq!(
+            get_nth_weekday(2026, 10, -1, Weekday::Fri),
+            NaiveDate::from_ymd_opt(2026, 10, 30)
+        );
+        assert_eq!(
+            get_nth_weekday(2026, 10, -1, Weekday::Sat),
+            NaiveDate::from_ymd_opt(2026, 10, 31)
+        );
+        assert_eq!(
+            get_last_business_day(2026, 10),
+            NaiveDate::from_ymd_opt(2026, 10, 30)
+        );
+
+        let slots = get_monthly_rule_slots(NaiveDate::from_ymd_opt(2026, 10, 30).unwrap());
+        assert_eq!(
+            slots,
+            [
+                "/mnt/data/recurring/monthly/last-business-day",
+                "/mnt/data/recurring/monthly/last-friday"
+            ]
+        );
+
+        let schedule = Schedule::MonthlyNthWeekday {
+            nth: 2,
+            weekday: Weekday::Tue,
+            time: "09:00".to_owned(),
+        };
+        assert_eq!(
+            schedule.get_file_location(&"job".to_owned()),
+            "/mnt/data/recurring/monthly/second-tuesday/09:00/job"
+        );
+    }
+
+    #[test]
+    pub fn test_schedule_from_file_location() {
+        let schedules = [
+            Schedule::Daily {
+                time: "08:00,18:00".to_owned(),
+            },
+            Schedule::Weekly {
+                weekday: Weekday::Tue,
+                time: "every-2h-08:00-18:00".to_owned(),
+            },
+            Schedule::Interval {
+                every: 3,
+                unit: IntervalUnit::Weeks,
+                anchor: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+                time: "09:00".to_owned(),
+            },
+            Schedule::MonthlyNthWeekday {
+                nth: -1,
+                weekday: Weekday::Fri,
+                time: "09:00".to_owned(),
+            },
+            Schedule::Yearly {
+                day: 29,
+                month: Month::February,
+                time: "09:00".to_owned(),
+            },
+        ];
+
+        for schedule in schedules {
+            let path = schedule.clone().get_file_location(&"id".to_owned());
+            assert_eq!(Schedule::from_file_location(&path), Some(schedule));
+        }
+        assert_eq!(
+            Schedule::from_file_location("/mnt/data/recurring/biweekly/0/monday/09:00/id"),
+            None
+        );
+    }
+
+    #[test]
+    pub fn test_times() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+
+        assert_eq!(parse_times("9:05"), Some(vec![time(9, 5)]));
+        assert_eq!(
+            parse_times("every-3h-08:30-18:00"),
+            Some(vec![time(8, 30), time(11, 30), time(14, 30), time(17, 30)])
+        );
+        assert_eq!(parse_times("every-0h-08:00-18:00"), None);
+        assert_eq!(parse_times("12:00,nope"), None);
+
+        let spec = format_times(&[time(18, 0), time(8, 0), time(12, 0), time(8, 0)]);
+        assert_eq!(spec, "08:00,12:00,18:00");
+        assert_eq!(get_times_display(&spec), "at 08:00, 12:00 and 18:00");
+        assert_eq!(get_times_display("09:00"), "at 09:00");
+        assert_eq!(
+            get_times_display(&format_time_range(2, time(8, 0), time(18, 0))),
+            "every 2 hours from 08:00 to 18:00"
+        );
+    }
+
+    #[test]
+    pub fn test_limits() {
+        let mut config = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        assert_eq!(config.get_end_reason(date(10, 1)), None);
+        assert_eq!(config.get_limits_display(), None);
+
+        config.end_date = Some(date(10, 31));
+        config.max_deliveries = Some(3);
+        config.occurrence_count = 2;
+        assert_eq!(config.get_end_reason(date(10, 31)), None);
+        assert_eq!(
+            config.get_end_reason(date(11, 1)),
+            Some("its end date 2026-10-31 has passed".to_owned())
+        );
+
+        config.occurrence_count = 3;
+        assert_eq!(
+            config.get_end_reason(date(10, 1)),
+            Some("it was sent 3 times".to_owned())
+        );
+        assert_eq!(
+            config.get_limits_display(),
+            Some("until 2026-10-31, 3 of 3 sent".to_owned())
+        );
+
+        // written as plain dates
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("end_date: 2026-10-31"));
+
+        // a saturday
+        assert!(!config.is_skipped(date(10, 17)));
+        config.skip_weekends = true;
+        config.skip_dates.push(date(10, 19));
+        assert!(config.is_skipped(date(10, 17)));
+        assert!(config.is_skipped(date(10, 19)));
+        assert!(!config.is_skipped(date(10, 20)));
+        assert_eq!(
+            config.get_skips_display(),
+            Some("weekends, 2026-10-19".to_owned())
+        );
+    }
 
     #[test]
     pub fn test_daily() {
         let schedule: Schedule = Schedule::Daily {
             time: "12:02".to_owned(),
         };
-        let res = schedule.get_file_location("test".as_ref());
+        let res = schedule.get_file_location(&"test".to_owned());
 
         assert_eq!(res, "/mnt/data/recurring/daily/12:02/test")
     }
@@ -221,7 +2662,7 @@ pub mod tests {
             weekday: Weekday::Mon,
             time: "3:02".to_owned(),
         };
-        let res = schedule.get_file_location("test2".as_ref());
+        let res = schedule.get_file_location(&"test2".to_owned());
 
         assert_eq!(res, "/mnt/data/recurring/weekly/monday/3:02/test2")
     }
@@ -232,21 +2673,68 @@ pub mod tests {
             day: 3,
             time: "10:56".to_owned(),
         };
-        let res = schedule.get_file_location("alan".as_ref());
+        let res = schedule.get_file_location(&"alan".to_owned());
 
         assert_eq!(res, "/mnt/data/recurring/monthly/3/10:56/alan")
     }
 
     #[test]
     pub fn test_biweekly() {
-        let schedule: Schedule = Schedule::BiWeekly {
-            weekday: Weekday::Tue,
+        let schedule: Schedule = Schedule::Interval {
+            every: 2,
+            unit: IntervalUnit::Weeks,
+            anchor: NaiveDate::from_ymd_opt(2026, 12, 29).unwrap(),
             time: "00:00".to_owned(),
-            odd: true,
         };
-        let res = schedule.get_file_location("bob".as_ref());
+        let res = schedule.get_file_location(&"bob".to_owned());
+
+        assert_eq!(
+            "/mnt/data/recurring/interval/weeks/2/2026-12-29/00:00/bob",
+            res
+        )
+    }
+
+    #[test]
+    pub fn test_interval_due() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // 2026 has 53 ISO weeks, two weeks on is still two weeks on
+        let anchor = date(2026, 12, 29);
+        assert_eq!(
+            is_interval_due(2, IntervalUnit::Weeks, anchor, date(2027, 1, 12)),
+            Some(None)
+        );
+        assert_eq!(
+            is_interval_due(2, IntervalUnit::Weeks, anchor, date(2027, 1, 5)),
+            None
+        );
+        assert_eq!(
+            is_interval_due(3, IntervalUnit::Weeks, anchor, date(2027, 1, 19)),
+            Some(None)
+        );
+        assert_eq!(
+            is_interval_due(10, IntervalUnit::Days, anchor, date(2026, 12, 19)),
+            None
+        );
 
-        assert_eq!("/mnt/data/recurring/biweekly/1/tuesday/00:00/bob", res)
+        // every other month from january 31st
+        let anchor = date(2027, 1, 31);
+        assert_eq!(
+            is_interval_due(2, IntervalUnit::Months, anchor, date(2027, 3, 31)),
+            Some(None)
+        );
+        assert_eq!(
+            is_interval_due(1, IntervalUnit::Months, anchor, date(2027, 2, 28)),
+            Some(Some(MissingDay::LastDay))
+        );
+        assert_eq!(
+            is_interval_due(1, IntervalUnit::Months, anchor, date(2027, 3, 1)),
+            Some(Some(MissingDay::NextDay))
+        );
+        assert_eq!(
+            is_interval_due(2, IntervalUnit::Months, anchor, date(2027, 2, 28)),
+            None
+        );
     }
 
     #[test]
@@ -256,8 +2744,138 @@ pub mod tests {
             time: "10:00".to_owned(),
             month: Month::December,
         };
-        let res: String = schedule.get_file_location("santa".as_ref());
+        let res: String = schedule.get_file_location(&"santa".to_owned());
 
         assert_eq!("/mnt/data/recurring/yearly/12/25/10:00/santa", res)
     }
+
+    #[test]
+    pub fn test_single_message_file() {
+        let config: Config =
+            serde_yaml::from_str("chat_id: '@chat'\nmessage: !Photo abc\n").unwrap();
+
+        assert_eq!(config.targets, vec![Target::new("@chat".to_owned())]);
+        assert_eq!(config.messages, vec![Message::Photo("abc".to_owned())]);
+        assert_eq!(config.selection, Selection::RoundRobin);
+    }
+
+    #[test]
+    pub fn test_multiple_targets_round_trip() {
+        let config = Config::new(
+            vec![
+                Target::new("@first".to_owned()),
+                Target::in_thread("-100123".to_owned(), 7),
+            ],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+
+        let reloaded: Config =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reloaded.targets, config.targets);
+        assert_eq!(reloaded.targets[1].thread_id, Some(7));
+    }
+
+    #[test]
+    pub fn test_owner_falls_back_to_file_name() {
+        let mut config = Config::new(
+            vec![Target::new("@chat".to_owned())],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+        let path = "/mnt/data/recurring/daily/09:00/1234".to_owned();
+
+        assert_eq!(get_owner(&path, &config), "1234");
+
+        config.owner = Some("42".to_owned());
+        assert_eq!(get_owner(&path, &config), "42");
+    }
+
+    #[test]
+    pub fn test_write_atomic() {
+        let directory = std::env::temp_dir().join(format!("event_manager_{}", new_job_id()));
+        create_dir_all(&directory).unwrap();
+        let path = directory.join("job").to_string_lossy().to_string();
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+
+        assert_eq!(read_to_string(&path).unwrap(), "second");
+        // no temp file is left behind
+        assert_eq!(directory.read_dir().unwrap().count(), 1);
+
+        // an existing file isn't replaced
+        assert!(write_new(&path, "third").is_err());
+        write_new(&format!("{}.new", path), "third").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "second");
+        assert_eq!(directory.read_dir().unwrap().count(), 2);
+
+        _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    pub fn test_job_ids() {
+        let mut config = Config::new(
+            vec![Target::new("@chat".to_owned())],
+            vec![Message::Message("hi".to_owned())],
+            Selection::RoundRobin,
+        );
+        config.id = Some("01hzx3v9q8k2m4n6p8r0t2v4x6".to_owned());
+
+        assert_eq!(config.short_id(), "r0t2v4x6");
+        assert!(config.has_id("R0T2V4X6"));
+        assert!(config.has_id("01hzx3v9q8k2m4n6p8r0t2v4x6"));
+        assert!(!config.has_id("4x6"));
+        assert!(!config.has_id("2"));
+
+        let other = Config::new(Vec::new(), Vec::new(), Selection::RoundRobin);
+        assert_ne!(other.id, config.id);
+    }
+
+    #[test]
+    pub fn test_round_robin() {
+        let mut config = Config::new(
+            vec![Target::new("@chat".to_owned())],
+            vec![
+                Message::Message("a".to_owned()),
+                Message::Message("b".to_owned()),
+            ],
+            Selection::RoundRobin,
+        );
+
+        assert_eq!(
+            config.next_message(),
+            Some(Message::Message("a".to_owned()))
+        );
+        assert_eq!(
+            config.next_message(),
+            Some(Message::Message("b".to_owned()))
+        );
+
+        let reloaded: Config =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reloaded.cursor, 0);
+    }
+
+    #[test]
+    pub fn test_shuffle_exhausts_before_repeat() {
+        let messages = (0..5)
+            .map(|i| Message::Message(i.to_string()))
+            .collect::<Vec<Message>>();
+        let mut config = Config::new(
+            vec![Target::new("@chat".to_owned())],
+            messages.clone(),
+            Selection::Shuffle,
+        );
+
+        let mut sent = (0..5)
+            .map(|_| config.next_message().unwrap())
+            .collect::<Vec<Message>>();
+        sent.sort_by_key(|m| match m {
+            Message::Message(text) => text.to_owned(),
+            _ => String::new(),
+        });
+
+        assert_eq!(sent, messages);
+    }
 }
@@ -0,0 +1,172 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+/// Values available to placeholders when a scheduled text is delivered.
+pub struct TemplateContext {
+    /// delivery time, already in the job's timezone
+    pub now: NaiveDateTime,
+    pub occurrence_count: u32,
+    pub chat_title: String,
+}
+
+enum Token {
+    Text(String),
+    Date,
+    Weekday,
+    WeekNumber,
+    DaysUntil(NaiveDate),
+    OccurrenceCount,
+    ChatTitle,
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Token, String> {
+    let (name, argument) = match placeholder.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (placeholder, None),
+    };
+
+    match (name, argument) {
+        ("date", None) => Ok(Token::Date),
+        ("weekday", None) => Ok(Token::Weekday),
+        ("week_number", None) => Ok(Token::WeekNumber),
+        ("occurrence_count", None) => Ok(Token::OccurrenceCount),
+        ("chat_title", None) => Ok(Token::ChatTitle),
+        ("days_until", Some(date)) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => Ok(Token::DaysUntil(date)),
+            Err(_) => Err(format!(
+                "{{days_until:{}}} needs a date written as year-month-day, like {{days_until:2026-12-25}}.",
+                date
+            )),
+        },
+        _ => Err(format!(
+            "I don't know the placeholder {{{}}}. Available are {{date}}, {{weekday}}, {{week_number}}, {{days_until:YYYY-MM-DD}}, {{occurrence_count}} and {{chat_title}}. Use {{{{ and }}}} for literal braces.",
+            placeholder
+        )),
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                current.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                current.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(format!(
+                                "The placeholder {{{} is never closed.",
+                                placeholder
+                            ))
+                        }
+                    }
+                }
+
+                if !current.is_empty() {
+                    tokens.push(Token::Text(current));
+                    current = String::new();
+                }
+                tokens.push(parse_placeholder(placeholder.trim())?);
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(Token::Text(current));
+    }
+
+    Ok(tokens)
+}
+
+/// Checks that every placeholder in `text` is known, so mistakes are caught
+/// when the job is created rather than when it is sent.
+pub fn validate_template(text: &str) -> Result<(), String> {
+    parse(text).map(|_| ())
+}
+
+/// Whether `text` uses {chat_title}, which takes a lookup to fill in.
+pub fn uses_chat_title(text: &str) -> bool {
+    parse(text).is_ok_and(|tokens| tokens.iter().any(|token| matches!(token, Token::ChatTitle)))
+}
+
+pub fn render_template(text: &str, context: &TemplateContext) -> String {
+    let tokens = match parse(text) {
+        Ok(tokens) => tokens,
+        // jobs saved before templates existed are sent verbatim
+        Err(_) => return text.to_owned(),
+    };
+
+    let today = context.now.date();
+
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            Token::Text(text) => text,
+            Token::Date => today.format("%Y-%m-%d").to_string(),
+            Token::Weekday => today.format("%A").to_string(),
+            Token::WeekNumber => today.iso_week().week().to_string(),
+            Token::DaysUntil(date) => (date - today).num_days().to_string(),
+            Token::OccurrenceCount => context.occurrence_count.to_string(),
+            Token::ChatTitle => context.chat_title.to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            now: NaiveDate::from_ymd_opt(2026, 10, 13)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            occurrence_count: 4,
+            chat_title: "Team".to_owned(),
+        }
+    }
+
+    #[test]
+    pub fn test_render() {
+        let res = render_template(
+            "{weekday} {date}, week {week_number}: {days_until:2026-12-25} days left, #{occurrence_count} in {chat_title}",
+            &context(),
+        );
+
+        assert_eq!("Tuesday 2026-10-13, week 42: 73 days left, #4 in Team", res)
+    }
+
+    #[test]
+    pub fn test_escaped_braces() {
+        assert_eq!("{date}", render_template("{{date}}", &context()))
+    }
+
+    #[test]
+    pub fn test_uses_chat_title() {
+        assert!(uses_chat_title("Hello { chat_title }"));
+        assert!(!uses_chat_title("Hello {{chat_title}}"));
+        assert!(!uses_chat_title("Hello {date}"));
+    }
+
+    #[test]
+    pub fn test_validate() {
+        assert!(validate_template("Hello {weekday}").is_ok());
+        assert!(validate_template("Hello {name}").is_err());
+        assert!(validate_template("Hello {days_until:tomorrow}").is_err());
+        assert!(validate_template("Hello {date").is_err());
+    }
+}
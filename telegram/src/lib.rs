@@ -1,23 +1,41 @@
 pub extern crate chatterbox;
 
+use chatterbox::accept_group_message;
 use chatterbox::accept_incoming_message;
+use chatterbox::get_wizard_session;
+use chatterbox::ChatDirectory;
 use chatterbox::Coorespondance;
 use chatterbox::FlowStatus;
 use chatterbox::Message as ChatterMessage;
 use chatterbox::OptionType;
+use chatterbox::BACK;
+use chrono::Utc;
+use event_manager::Target;
 use reqwest::Client;
 use reqwest::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::to_string;
 use std::env;
 
+mod picker;
+
+use picker::{handle_picker_data, PickerAction};
+
 #[derive(Serialize, Deserialize)]
 struct File {
     file_id: String,
+    // only in the answer to getFile
+    file_path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GetFile {
+    file_id: String,
 }
 #[derive(Serialize, Deserialize)]
 struct User {
     id: i64,
+    username: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -32,6 +50,41 @@ struct Chat {
 struct GetChat {
     chat_id: String,
 }
+
+#[derive(Serialize, Deserialize)]
+struct ChatInfo {
+    id: i64,
+    #[serde(rename = "type")]
+    _type: Option<String>,
+    title: Option<String>,
+    username: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GetChatMember {
+    chat_id: String,
+    user_id: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatMember {
+    status: String,
+    can_post_messages: Option<bool>,
+    can_send_messages: Option<bool>,
+    is_member: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MessageOrigin {
+    chat: Option<Chat>,
+    sender_chat: Option<Chat>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatShared {
+    request_id: i32,
+    chat_id: i64,
+}
 #[derive(Serialize, Deserialize)]
 struct CallbackQuery {
     id: String,
@@ -53,28 +106,84 @@ struct SendMessage {
     text: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    reply_markup: Option<InlineKeyboardMarkup>,
+    reply_markup: Option<ReplyMarkup>,
 }
 
 #[derive(Serialize, Deserialize)]
 #[serde(untagged)]
 enum SendMedia {
-    Photo { chat_id: String, photo: String },
-    Video { chat_id: String, video: String },
-    Audio { chat_id: String, audio: String },
-    Document { chat_id: String, document: String },
-    Voice { chat_id: String, voice: String },
+    Text {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        text: String,
+    },
+    Photo {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        photo: String,
+    },
+    Video {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        video: String,
+    },
+    Audio {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        audio: String,
+    },
+    Document {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        document: String,
+    },
+    Voice {
+        chat_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_thread_id: Option<i64>,
+        voice: String,
+    },
 }
 #[derive(Serialize, Deserialize)]
 struct Message {
+    message_id: Option<i64>,
     chat: Option<Chat>,
     from: Option<User>,
     text: Option<String>,
     video: Option<Vec<File>>,
-    document: Option<Vec<File>>,
+    document: Option<File>,
     photo: Option<Vec<File>>,
     entities: Option<Vec<Entity>>,
     voice: Option<File>,
+    forward_from_chat: Option<Chat>,
+    forward_origin: Option<MessageOrigin>,
+    chat_shared: Option<ChatShared>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EditMessageReplyMarkup {
+    chat_id: i64,
+    message_id: i64,
+    reply_markup: InlineKeyboardMarkup,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EditMessageText {
+    chat_id: i64,
+    message_id: i64,
+    text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AnswerCallbackQuery {
+    callback_query_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -94,6 +203,34 @@ struct Values {
     base_url: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ReplyMarkup {
+    Inline(InlineKeyboardMarkup),
+    Keyboard(ReplyKeyboardMarkup),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplyKeyboardMarkup {
+    keyboard: Vec<Vec<KeyboardButton>>,
+    one_time_keyboard: bool,
+    resize_keyboard: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyboardButton {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_chat: Option<KeyboardButtonRequestChat>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyboardButtonRequestChat {
+    request_id: i32,
+    chat_is_channel: bool,
+    bot_is_member: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 struct InlineKeyboardMarkup {
     inline_keyboard: Vec<Vec<InlineKeyboardButton>>,
@@ -121,6 +258,18 @@ impl Values {
         result
     }
 
+    pub fn get_url_edit_markup(&self, token: &String) -> String {
+        format!("{}{}/editMessageReplyMarkup", self.base_url, token)
+    }
+
+    pub fn get_url_edit_text(&self, token: &String) -> String {
+        format!("{}{}/editMessageText", self.base_url, token)
+    }
+
+    pub fn get_url_answer_query(&self, token: &String) -> String {
+        format!("{}{}/answerCallbackQuery", self.base_url, token)
+    }
+
     pub fn get_url_updates(&self, token: &String) -> String {
         format!("{}{}/getUpdates", self.base_url, token)
     }
@@ -129,6 +278,10 @@ impl Values {
         format!("{}{}/getChat", self.base_url, token)
     }
 
+    pub fn get_url_chat_member(&self, token: &String) -> String {
+        format!("{}{}/getChatMember", self.base_url, token)
+    }
+
     pub fn get_url_photo(&self, token: &String) -> String {
         format!("{}{}/sendPhoto", self.base_url, token)
     }
@@ -148,6 +301,24 @@ impl Values {
     pub fn get_url_voice(&self, token: &String) -> String {
         format!("{}{}/sendVoice", self.base_url, token)
     }
+
+    pub fn get_url_file(&self, token: &String) -> String {
+        format!("{}{}/getFile", self.base_url, token)
+    }
+
+    pub fn get_url_me(&self, token: &String) -> String {
+        format!("{}{}/getMe", self.base_url, token)
+    }
+
+    // files are downloaded from /file/bot<token>/<path>
+    pub fn get_url_download(&self, token: &String, file_path: &String) -> String {
+        format!(
+            "{}{}/{}",
+            self.base_url.replacen("/bot", "/file/bot", 1),
+            token,
+            file_path
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -156,6 +327,9 @@ struct Response<T> {
     result: T,
 }
 
+/// Outcome of a delivery per target, keyed by the target's display name.
+pub type DeliveryResults = Vec<(String, ::core::result::Result<(), String>)>;
+
 pub struct BotBoy {
     token: String,
     values: Values,
@@ -176,29 +350,89 @@ impl BotBoy {
             ChatterMessage::Message(text) => {
                 _ = self.send_message_to_user(user_id, &text);
             }
-            ChatterMessage::Photo(id) => match self.send_photo(&user_id.to_string(), &id) {
+            ChatterMessage::Photo(id) => match self.send_photo(&user_id.to_string(), None, &id) {
                 Ok(()) => {}
                 Err(_e) => {}
             },
-            ChatterMessage::Audio(id) => match self.send_audio(&user_id.to_string(), &id) {
+            ChatterMessage::Audio(id) => match self.send_audio(&user_id.to_string(), None, &id) {
                 Ok(()) => {}
                 Err(_e) => {}
             },
-            ChatterMessage::Video(id) => match self.send_video(&user_id.to_string(), &id) {
+            ChatterMessage::Video(id) => match self.send_video(&user_id.to_string(), None, &id) {
                 Ok(()) => {}
                 Err(_e) => {}
             },
-            ChatterMessage::Document(id) => match self.send_document(&user_id.to_string(), &id) {
-                Ok(()) => {}
-                Err(_e) => {}
-            },
-            ChatterMessage::Voice(id) => match self.send_voice(&user_id.to_string(), &id) {
+            ChatterMessage::Document(id) => {
+                match self.send_document(&user_id.to_string(), None, &id) {
+                    Ok(()) => {}
+                    Err(_e) => {}
+                }
+            }
+            ChatterMessage::Voice(id) => match self.send_voice(&user_id.to_string(), None, &id) {
                 Ok(()) => {}
                 Err(_e) => {}
             },
         }
     }
 
+    /// Sends the next message of the schedule file at `path` to each of its chats.
+    /// Returns the result of every target, keyed by chat id.
+    pub fn deliver(&self, path: &String) -> ::core::result::Result<DeliveryResults, String> {
+        let (config, message) = event_manager::take_message(path)?;
+
+        let needs_title = match &message {
+            ChatterMessage::Message(text) => event_manager::uses_chat_title(text),
+            _ => false,
+        };
+
+        let now = Utc::now();
+        let mut results = Vec::new();
+
+        for target in config.targets.iter() {
+            let chat_title = if needs_title {
+                self.get_chat_title(&target.chat_id)
+                    .unwrap_or(target.chat_id.to_owned())
+            } else {
+                target.chat_id.to_owned()
+            };
+
+            let rendered = config.render(message.to_owned(), &now, &chat_title);
+
+            results.push((
+                target.to_string(),
+                self.send_media_to_chat(target, &rendered),
+            ));
+        }
+
+        Ok(results)
+    }
+
+    pub fn send_media_to_chat(
+        &self,
+        target: &Target,
+        message: &ChatterMessage,
+    ) -> ::core::result::Result<(), String> {
+        match message {
+            ChatterMessage::Message(text) => {
+                let url = self.values.get_url_send(&self.token);
+                let media = SendMedia::Text {
+                    chat_id: target.chat_id.to_owned(),
+                    message_thread_id: target.thread_id,
+                    text: text.to_owned(),
+                };
+
+                self.send_media(media, &url)
+            }
+            ChatterMessage::Photo(id) => self.send_photo(&target.chat_id, target.thread_id, id),
+            ChatterMessage::Audio(id) => self.send_audio(&target.chat_id, target.thread_id, id),
+            ChatterMessage::Video(id) => self.send_video(&target.chat_id, target.thread_id, id),
+            ChatterMessage::Document(id) => {
+                self.send_document(&target.chat_id, target.thread_id, id)
+            }
+            ChatterMessage::Voice(id) => self.send_voice(&target.chat_id, target.thread_id, id),
+        }
+    }
+
     pub fn send_message_to_user(
         &self,
         user_id: i64,
@@ -213,6 +447,26 @@ impl BotBoy {
         self.send_object(&self.values.get_url_send(&self.token), send_message)
     }
 
+    /// Uploads `content` as a document called `name`, e.g. an export.
+    pub fn send_file_to_user(
+        &self,
+        user_id: i64,
+        name: &String,
+        content: String,
+    ) -> Result<reqwest::Response> {
+        let form = reqwest::multipart::Form::new()
+            .text("chat_id", user_id.to_string())
+            .part(
+                "document",
+                reqwest::multipart::Part::text(content).file_name(name.to_owned()),
+            );
+
+        self.client
+            .post(self.values.get_url_document(&self.token).as_str())
+            .multipart(form)
+            .send()
+    }
+
     pub fn send_message_to_user_with_yesno(
         &self,
         user_id: i64,
@@ -250,17 +504,124 @@ impl BotBoy {
             index += 1;
         }
 
+        self.send_message_to_user_with_keyboard(user_id, message, items)
+    }
+
+    fn send_message_to_user_with_keyboard(
+        &self,
+        user_id: i64,
+        message: &String,
+        keyboard: Vec<Vec<InlineKeyboardButton>>,
+    ) -> Result<reqwest::Response> {
         let message = SendMessage {
             chat_id: user_id,
             text: message.to_string(),
-            reply_markup: Some(InlineKeyboardMarkup {
-                inline_keyboard: items,
-            }),
+            reply_markup: Some(ReplyMarkup::Inline(InlineKeyboardMarkup {
+                inline_keyboard: tag_keyboard(keyboard, get_wizard_session(&user_id.to_string())),
+            })),
         };
 
         self.send_object(&&self.values.get_url_send(&self.token), message)
     }
 
+    /// Replaces the buttons below a message that was already sent.
+    fn edit_keyboard(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        keyboard: Vec<Vec<InlineKeyboardButton>>,
+    ) -> Result<reqwest::Response> {
+        let edit = EditMessageReplyMarkup {
+            chat_id,
+            message_id,
+            reply_markup: InlineKeyboardMarkup {
+                inline_keyboard: tag_keyboard(keyboard, get_wizard_session(&chat_id.to_string())),
+            },
+        };
+
+        self.send_object(&self.values.get_url_edit_markup(&self.token), edit)
+    }
+
+    /// Swaps the buttons of an answered question for the answer that was picked.
+    fn close_keyboard(&self, message: &Message, choice: Option<&str>) {
+        let chat_id = message.chat.as_ref().unwrap().id;
+        let message_id = match message.message_id {
+            Some(message_id) => message_id,
+            None => return,
+        };
+
+        match (choice, &message.text) {
+            (Some(choice), Some(text)) => {
+                let edit = EditMessageText {
+                    chat_id,
+                    message_id,
+                    text: format!("{}\n\n» {}", text, choice),
+                };
+                _ = self.send_object(&self.values.get_url_edit_text(&self.token), edit);
+            }
+            _ => {
+                _ = self.edit_keyboard(chat_id, message_id, Vec::new());
+            }
+        }
+    }
+
+    // stops the client's loading spinner on the pressed button
+    fn answer_query(&self, query_id: &str, text: Option<&str>) {
+        let answer = AnswerCallbackQuery {
+            callback_query_id: query_id.to_owned(),
+            text: text.map(|text| text.to_owned()),
+        };
+
+        _ = self.send_object(&self.values.get_url_answer_query(&self.token), answer);
+    }
+
+    /// Shows a keyboard that lets the user pick one of their groups or channels.
+    pub fn send_message_to_user_with_chat_request(
+        &self,
+        user_id: i64,
+        message: &String,
+        back: bool,
+    ) -> Result<reqwest::Response> {
+        let mut keyboard = vec![vec![
+            KeyboardButton {
+                text: "Pick a group".to_owned(),
+                request_chat: Some(KeyboardButtonRequestChat {
+                    request_id: 1,
+                    chat_is_channel: false,
+                    bot_is_member: true,
+                }),
+            },
+            KeyboardButton {
+                text: "Pick a channel".to_owned(),
+                request_chat: Some(KeyboardButtonRequestChat {
+                    request_id: 2,
+                    chat_is_channel: true,
+                    bot_is_member: true,
+                }),
+            },
+        ]];
+
+        // a plain button, pressing it sends its text
+        if back {
+            keyboard.push(vec![KeyboardButton {
+                text: BACK.to_owned(),
+                request_chat: None,
+            }]);
+        }
+
+        let message = SendMessage {
+            chat_id: user_id,
+            text: message.to_string(),
+            reply_markup: Some(ReplyMarkup::Keyboard(ReplyKeyboardMarkup {
+                keyboard,
+                one_time_keyboard: true,
+                resize_keyboard: true,
+            })),
+        };
+
+        self.send_object(&self.values.get_url_send(&self.token), message)
+    }
+
     pub fn send_object<T: Serialize>(&self, url: &String, object: T) -> Result<reqwest::Response> {
         let body = to_string(&object).unwrap();
 
@@ -347,20 +708,77 @@ impl BotBoy {
     }
 
     fn handle_query_update(&self, i: Update) {
-        let query = i.callback_query.unwrap();
+        let mut query = i.callback_query.unwrap();
+        let chat_id = query.message.chat.as_ref().unwrap().id;
+
+        // buttons left over from an earlier wizard
+        let session = get_wizard_session(&chat_id.to_string());
+        let data = match untag(&query.data, session.as_deref()) {
+            Some(data) => data.to_owned(),
+            None => {
+                self.answer_query(&query.id, Some("This question has expired."));
+                self.close_keyboard(&query.message, None);
+                return;
+            }
+        };
+
+        // paging through a time or date picker stays within the message
+        match handle_picker_data(&data, Utc::now().date_naive()) {
+            Some(PickerAction::Edit(keyboard)) => {
+                self.answer_query(&query.id, None);
+                if let Some(message_id) = query.message.message_id {
+                    _ = self.edit_keyboard(chat_id, message_id, keyboard);
+                }
+                return;
+            }
+            Some(PickerAction::Ignore) => {
+                self.answer_query(&query.id, None);
+                return;
+            }
+            None => {}
+        }
+
+        self.answer_query(&query.id, None);
+        self.close_keyboard(&query.message, Some(&data));
+
+        query.data = data;
         let (text, chat_id) = get_string_from_query(query);
-        self.update_from_message(chat_id, &ChatterMessage::Message(text));
+        self.update_from_message(chat_id, &ChatterMessage::Message(text), None);
     }
 
     fn handle_message_update(&self, i: Update) {
         let message = i.message.unwrap();
         let (text, chat_id) = self.get_string_from_message(&message);
-        self.update_from_message(chat_id, &text);
+
+        let chat_type = message.chat.as_ref().and_then(|chat| chat._type.as_deref());
+        if let (Some(chat_type), Some(from)) = (chat_type, &message.from) {
+            if chat_type != "private" {
+                // only /jobs is answered in groups
+                let flow_status =
+                    accept_group_message(&chat_id.to_string(), &from.id.to_string(), &text, self);
+                if let Some(flow_status) = flow_status {
+                    self.respond(chat_id, flow_status);
+                }
+                return;
+            }
+        }
+
+        self.update_from_message(chat_id, &text, get_forwarded_from(&message));
     }
 
-    fn update_from_message(&self, chat_id: i64, message: &ChatterMessage) {
-        let flow_status = accept_incoming_message(&chat_id.to_string(), message);
+    fn update_from_message(
+        &self,
+        chat_id: i64,
+        message: &ChatterMessage,
+        forwarded_from: Option<String>,
+    ) {
+        let flow_status =
+            accept_incoming_message(&chat_id.to_string(), message, forwarded_from, self);
+
+        self.respond(chat_id, flow_status);
+    }
 
+    fn respond(&self, chat_id: i64, flow_status: FlowStatus) {
         match flow_status {
             FlowStatus::Cancelled => {
                 let _ = self.send_message_to_user(
@@ -387,13 +805,51 @@ impl BotBoy {
             FlowStatus::Info(message) => {
                 _ = self.send_message_to_user(chat_id, &message);
             }
-            FlowStatus::Media(message) => {
-                _ = self.send_media_to_user(chat_id, message);
+            FlowStatus::Media(messages) => {
+                for message in messages {
+                    self.send_media_to_user(chat_id, message);
+                }
+            }
+            FlowStatus::Preview(messages, coorespondance) => {
+                for message in messages {
+                    self.send_media_to_user(chat_id, message);
+                }
+                self.use_coorespondance(chat_id, coorespondance);
+            }
+            FlowStatus::File { name, content } => {
+                _ = self.send_file_to_user(chat_id, &name, content);
             }
         }
     }
 
     fn use_coorespondance(&self, chat_id: i64, coorespondance: Coorespondance) {
+        // the pickers come with their own back button
+        match coorespondance.option_type {
+            OptionType::Time(picked) => {
+                _ = self.send_message_to_user_with_keyboard(
+                    chat_id,
+                    &coorespondance.message,
+                    picker::time_picker(&picked),
+                );
+                return;
+            }
+            OptionType::Date => {
+                let today = Utc::now().date_naive();
+                _ = self.send_message_to_user_with_keyboard(
+                    chat_id,
+                    &coorespondance.message,
+                    picker::calendar(today, today),
+                );
+                return;
+            }
+            _ => {}
+        }
+
+        if coorespondance.back {
+            self.use_coorespondance_with_back(chat_id, coorespondance);
+            return;
+        }
+
         let _response: Option<::core::result::Result<::reqwest::Response, ::reqwest::Error>> =
             match coorespondance.option_type {
                 OptionType::Date => {
@@ -402,10 +858,15 @@ impl BotBoy {
                 OptionType::Media => {
                     Some(self.send_message_to_user(chat_id, &coorespondance.message))
                 }
+                OptionType::Chat => Some(self.send_message_to_user_with_chat_request(
+                    chat_id,
+                    &coorespondance.message,
+                    false,
+                )),
                 OptionType::YesNo => {
                     Some(self.send_message_to_user_with_yesno(chat_id, &coorespondance.message))
                 }
-                OptionType::Time => {
+                OptionType::Time(_) => {
                     Some(self.send_message_to_user(chat_id, &coorespondance.message))
                 }
                 OptionType::Options(options) => {
@@ -419,6 +880,33 @@ impl BotBoy {
             };
     }
 
+    // same as use_coorespondance, with an extra row holding the back button
+    fn use_coorespondance_with_back(&self, chat_id: i64, coorespondance: Coorespondance) {
+        let back = vec![BACK.to_owned()];
+
+        let mut options = match coorespondance.option_type {
+            OptionType::Chat => {
+                _ = self.send_message_to_user_with_chat_request(
+                    chat_id,
+                    &coorespondance.message,
+                    true,
+                );
+                return;
+            }
+            OptionType::YesNo => vec![vec!["Yes".to_owned(), "No".to_owned()]],
+            OptionType::Options(options) => options,
+            OptionType::None => return,
+            _ => Vec::new(),
+        };
+        options.push(back);
+
+        _ = self.send_message_to_user_with_option_response(
+            chat_id,
+            &coorespondance.message,
+            &options,
+        );
+    }
+
     fn get_updates(&self, input: &String) -> core::result::Result<Vec<Update>, ()> {
         match self.get_updates_webhook(input) {
             Ok(obj) => return Ok(obj),
@@ -492,6 +980,193 @@ impl BotBoy {
         }
     }
 
+    fn get_chat_info(&self, chat_id: &String) -> Option<ChatInfo> {
+        let url = self.values.get_url_chat(&self.token);
+
+        let response = self.send_object(
+            &url,
+            GetChat {
+                chat_id: chat_id.to_owned(),
+            },
+        );
+
+        let text = response.ok()?.text().ok()?;
+        let chat = serde_json::from_str::<Response<ChatInfo>>(&text).ok()?;
+
+        Some(chat.result)
+    }
+
+    // an error when telegram couldn't be asked, e.g. while rate limited
+    fn get_chat_member(
+        &self,
+        chat_id: &String,
+        user_id: i64,
+    ) -> ::core::result::Result<ChatMember, String> {
+        let url = self.values.get_url_chat_member(&self.token);
+
+        let response = self.send_object(
+            &url,
+            GetChatMember {
+                chat_id: chat_id.to_owned(),
+                user_id,
+            },
+        );
+
+        let text = response
+            .and_then(|mut response| response.text())
+            .map_err(|e| e.to_string())?;
+
+        match serde_json::from_str::<Response<ChatMember>>(&text) {
+            Ok(member) => Ok(member.result),
+            Err(_) => Err(format!("getChatMember failed: {}", text)),
+        }
+    }
+
+    /// Whether the user administers the chat, an error if that couldn't be checked.
+    fn is_admin(
+        &self,
+        chat_id: &String,
+        chat_type: Option<&str>,
+        u_id: &String,
+    ) -> ::core::result::Result<bool, String> {
+        let user_id = match u_id.parse::<i64>() {
+            Ok(id) => id,
+            Err(_) => return Ok(false),
+        };
+
+        self.get_chat_member(chat_id, user_id)
+            .map(|member| can_manage(chat_type, &member))
+    }
+
+    /// Disables the job at `path` if its owner is no longer an administrator of
+    /// every target chat, and lets the owner know. Returns whether it was disabled.
+    pub fn recheck_owner(&self, path: &String) -> ::core::result::Result<bool, String> {
+        let config = event_manager::load_config(path)?;
+
+        if config.disabled.is_some() {
+            return Ok(false);
+        }
+
+        let owner = event_manager::get_owner(path, &config);
+
+        // a failed lookup says nothing about the owner, so the job is left alone
+        let mut lost = None;
+        for target in config.targets.iter() {
+            let chat = self
+                .get_chat_info(&target.chat_id)
+                .ok_or(format!("could not look up {}", target))?;
+
+            if !self.is_admin(&target.chat_id, chat._type.as_deref(), &owner)? {
+                lost = Some(target);
+                break;
+            }
+        }
+
+        let reason = match lost {
+            Some(target) => format!("you are no longer an administrator of {}", target),
+            None => return Ok(false),
+        };
+
+        event_manager::update_config(path, |config| {
+            config.disabled = Some(reason.to_owned());
+            Ok(())
+        })?;
+
+        if let Ok(owner_id) = owner.parse::<i64>() {
+            _ = self.send_message_to_user(
+                owner_id,
+                &format!(
+                    "One of your scheduled messages has been disabled because {}.",
+                    reason
+                ),
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// Archives the job if it has ended and lets its owner know.
+    /// Returns whether it was archived.
+    pub fn archive_if_ended(&self, path: &String) -> ::core::result::Result<bool, String> {
+        let (config, reason) = match event_manager::archive_if_ended(path, &Utc::now())? {
+            Some(ended) => ended,
+            None => return Ok(false),
+        };
+
+        if let Ok(owner_id) = event_manager::get_owner(path, &config).parse::<i64>() {
+            _ = self.send_message_to_user(
+                owner_id,
+                &format!(
+                    "Your scheduled message [{}] has ended because {} and was archived.",
+                    config.short_id(),
+                    reason
+                ),
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// Sends what the job at `path` missed while the dispatcher was down, as
+    /// far as its catch up policy says, and tells the owner about the rest.
+    /// Returns how many were missed and how many sent late.
+    pub fn catch_up(&self, path: &String) -> Option<(usize, usize)> {
+        let missed = event_manager::take_missed(path, &Utc::now())?;
+
+        let mut sent = 0;
+        for _ in 0..missed.send {
+            match self.deliver(path) {
+                Ok(results) if results.iter().any(|(_, result)| result.is_ok()) => sent += 1,
+                Ok(_) => {}
+                Err(e) => println!("failed {}: {}", path, e),
+            }
+        }
+
+        if sent < missed.due.len() {
+            if let Ok(owner_id) = event_manager::get_owner(path, &missed.config).parse::<i64>() {
+                _ = self.send_message_to_user(
+                    owner_id,
+                    &format!(
+                        "Your scheduled message [{}] missed {} deliveries while the scheduler was down, the first due {}. {} of them were sent late.",
+                        missed.config.short_id(),
+                        missed.due.len(),
+                        missed.due[0]
+                            .with_timezone(&missed.config.get_timezone())
+                            .format("%a %-d %b %H:%M"),
+                        sent
+                    ),
+                );
+            }
+        }
+
+        Some((missed.due.len(), sent))
+    }
+
+    // the numeric part of the token is the bot's user id
+    fn get_bot_id(&self) -> i64 {
+        self.token
+            .split(':')
+            .next()
+            .and_then(|id| id.parse::<i64>().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn get_chat_title(&self, chat_id: &String) -> Option<String> {
+        let url = self.values.get_url_chat(&self.token);
+
+        let response = self.send_object(
+            &url,
+            GetChat {
+                chat_id: chat_id.to_owned(),
+            },
+        );
+
+        let text = response.ok()?.text().ok()?;
+        let chat = serde_json::from_str::<Response<ChatInfo>>(&text).ok()?;
+
+        chat.result.title.or(chat.result.username)
+    }
+
     fn send_media(&self, media: SendMedia, url: &String) -> ::core::result::Result<(), String> {
         match self.send_object(&url, media) {
             Ok(mut response) => {
@@ -512,12 +1187,14 @@ impl BotBoy {
     pub fn send_document(
         &self,
         chat_id: &String,
+        thread_id: Option<i64>,
         document: &String,
     ) -> ::core::result::Result<(), String> {
         let url = self.values.get_url_document(&self.token);
 
         let media = SendMedia::Document {
             chat_id: chat_id.to_owned(),
+            message_thread_id: thread_id,
             document: document.to_owned(),
         };
 
@@ -527,12 +1204,14 @@ impl BotBoy {
     pub fn send_audio(
         &self,
         chat_id: &String,
+        thread_id: Option<i64>,
         audio: &String,
     ) -> ::core::result::Result<(), String> {
         let url = self.values.get_url_audio(&self.token);
 
         let media = SendMedia::Audio {
             chat_id: chat_id.to_owned(),
+            message_thread_id: thread_id,
             audio: audio.to_owned(),
         };
 
@@ -542,12 +1221,14 @@ impl BotBoy {
     pub fn send_voice(
         &self,
         chat_id: &String,
+        thread_id: Option<i64>,
         voice: &String,
     ) -> ::core::result::Result<(), String> {
         let url = self.values.get_url_voice(&self.token);
 
         let media = SendMedia::Voice {
             chat_id: chat_id.to_owned(),
+            message_thread_id: thread_id,
             voice: voice.to_owned(),
         };
 
@@ -557,12 +1238,14 @@ impl BotBoy {
     pub fn send_video(
         &self,
         chat_id: &String,
+        thread_id: Option<i64>,
         video: &String,
     ) -> ::core::result::Result<(), String> {
         let url = self.values.get_url_video(&self.token);
 
         let media = SendMedia::Video {
             chat_id: chat_id.to_owned(),
+            message_thread_id: thread_id,
             video: video.to_owned(),
         };
 
@@ -572,12 +1255,14 @@ impl BotBoy {
     pub fn send_photo(
         &self,
         chat_id: &String,
+        thread_id: Option<i64>,
         photo: &String,
     ) -> ::core::result::Result<(), String> {
         let url = self.values.get_url_photo(&self.token);
 
         let media = SendMedia::Photo {
             chat_id: chat_id.to_owned(),
+            message_thread_id: thread_id,
             photo: photo.to_owned(),
         };
 
@@ -585,7 +1270,13 @@ impl BotBoy {
     }
 
     fn get_string_from_message(&self, message: &Message) -> (ChatterMessage, i64) {
-        if message.entities.is_some() {
+        if let Some(shared) = &message.chat_shared {
+            // reply to the chat request keyboard
+            (
+                ChatterMessage::Message(shared.chat_id.to_string()),
+                message.chat.as_ref().unwrap().id,
+            )
+        } else if message.entities.is_some() {
             let entities = message.entities.as_ref().unwrap();
             let entity = entities.get(0).unwrap();
             match entity._type.as_str() {
@@ -628,16 +1319,7 @@ impl BotBoy {
             )
         } else if message.document.is_some() {
             (
-                ChatterMessage::Document(
-                    message
-                        .document
-                        .as_ref()
-                        .unwrap()
-                        .get(0)
-                        .unwrap()
-                        .file_id
-                        .to_owned(),
-                ),
+                ChatterMessage::Document(message.document.as_ref().unwrap().file_id.to_owned()),
                 message.chat.as_ref().unwrap().id,
             )
         } else if message.photo.is_some() {
@@ -665,6 +1347,194 @@ impl BotBoy {
     }
 }
 
+impl ChatDirectory for BotBoy {
+    fn verify_chat(&self, target: Target, u_id: &String) -> ::core::result::Result<Target, String> {
+        let chat = match self.get_chat_info(&target.chat_id) {
+            Some(chat) => chat,
+            None => {
+                return Err(format!(
+                    "I couldn't find {}. Please make sure I've been added to it.",
+                    target.chat_id
+                ))
+            }
+        };
+
+        let title = chat
+            .title
+            .or(chat.username.map(|name| format!("@{}", name)));
+        let name = title.to_owned().unwrap_or(target.chat_id.to_owned());
+
+        let unchecked = |_| {
+            format!(
+                "I couldn't check the permissions in {}, please try again later.",
+                name
+            )
+        };
+
+        let allowed = self
+            .get_chat_member(&chat.id.to_string(), self.get_bot_id())
+            .map(|member| can_post(chat._type.as_deref(), &member))
+            .map_err(unchecked)?;
+
+        if !allowed {
+            return Err(format!(
+                "I'm not allowed to post in {}. Please add me there with permission to send messages, then try again.",
+                name
+            ));
+        }
+
+        if !self
+            .is_admin(&chat.id.to_string(), chat._type.as_deref(), u_id)
+            .map_err(unchecked)?
+        {
+            return Err(format!(
+                "Only administrators of {} who can post there may schedule messages to it.",
+                name
+            ));
+        }
+
+        Ok(Target {
+            chat_id: chat.id.to_string(),
+            thread_id: target.thread_id,
+            title,
+        })
+    }
+
+    fn read_document(&self, file_id: &String) -> Option<String> {
+        let response = self.send_object(
+            &self.values.get_url_file(&self.token),
+            GetFile {
+                file_id: file_id.to_owned(),
+            },
+        );
+
+        let text = response.ok()?.text().ok()?;
+        let file = serde_json::from_str::<Response<File>>(&text).ok()?;
+        let url = self
+            .values
+            .get_url_download(&self.token, &file.result.file_path?);
+
+        self.client.get(&url).send().ok()?.text().ok()
+    }
+
+    fn get_bot_name(&self, token: Option<&String>) -> Option<String> {
+        let url = self.values.get_url_me(token.unwrap_or(&self.token));
+
+        let text = self.client.get(&url).send().ok()?.text().ok()?;
+        let me = serde_json::from_str::<Response<User>>(&text).ok()?;
+
+        me.result.username.map(|name| format!("@{}", name))
+    }
+
+    fn is_chat_admin(&self, chat_id: &String, u_id: &String) -> bool {
+        match self.get_chat_info(chat_id) {
+            Some(chat) => self
+                .is_admin(&chat.id.to_string(), chat._type.as_deref(), u_id)
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+fn can_manage(chat_type: Option<&str>, member: &ChatMember) -> bool {
+    match member.status.as_str() {
+        "creator" => true,
+        "administrator" => match chat_type {
+            Some("channel") => member.can_post_messages.unwrap_or(false),
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
+fn can_post(chat_type: Option<&str>, member: &ChatMember) -> bool {
+    if can_manage(chat_type, member) {
+        return true;
+    }
+
+    match member.status.as_str() {
+        "member" => chat_type != Some("channel"),
+        "restricted" => {
+            member.is_member.unwrap_or(false) && member.can_send_messages.unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn get_forwarded_from(message: &Message) -> Option<String> {
+    let origin = message.forward_origin.as_ref().and_then(|origin| {
+        origin
+            .chat
+            .as_ref()
+            .or(origin.sender_chat.as_ref())
+            .map(|chat| chat.id)
+    });
+
+    origin
+        .or(message.forward_from_chat.as_ref().map(|chat| chat.id))
+        .map(|id| id.to_string())
+}
+
+// separates the wizard session from the value in callback data
+const SESSION_SEPARATOR: char = '|';
+
+fn tag_keyboard(
+    keyboard: Vec<Vec<InlineKeyboardButton>>,
+    session: Option<String>,
+) -> Vec<Vec<InlineKeyboardButton>> {
+    let session = match session {
+        Some(session) => session,
+        None => return keyboard,
+    };
+
+    keyboard
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|button| InlineKeyboardButton {
+                    callback_data: format!(
+                        "{}{}{}",
+                        session, SESSION_SEPARATOR, button.callback_data
+                    ),
+                    text: button.text,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The value of a button, or None when it belongs to another wizard than the
+/// current one. Untagged buttons are only accepted outside of a wizard.
+fn untag<'a>(data: &'a str, session: Option<&str>) -> Option<&'a str> {
+    match (data.split_once(SESSION_SEPARATOR), session) {
+        (Some((tag, value)), Some(session)) if tag == session => Some(value),
+        (None, None) => Some(data),
+        _ => None,
+    }
+}
+
 fn get_string_from_query(query: CallbackQuery) -> (String, i64) {
     (query.data, query.message.chat.unwrap().id)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_session_tags() {
+        let keyboard = vec![vec![InlineKeyboardButton {
+            text: "Yes".to_owned(),
+            callback_data: "Yes".to_owned(),
+        }]];
+        let tagged = tag_keyboard(keyboard, Some("abc123".to_owned()));
+        let data = &tagged[0][0].callback_data;
+
+        assert_eq!(data, "abc123|Yes");
+        assert_eq!(untag(data, Some("abc123")), Some("Yes"));
+        assert_eq!(untag(data, Some("zzz999")), None);
+        assert_eq!(untag(data, None), None);
+        assert_eq!(untag("Yes", Some("abc123")), None);
+        assert_eq!(untag("Yes", None), Some("Yes"));
+    }
+}
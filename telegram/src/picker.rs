@@ -0,0 +1,237 @@
+use crate::InlineKeyboardButton;
+use chatterbox::BACK;
+use chrono::{Datelike, Months, NaiveDate};
+
+// buttons that only label the grid
+const NOOP: &str = "noop";
+
+const CALENDAR: &str = "cal:";
+const HOURS: &str = "time:hours";
+const HOUR: &str = "time:hour:";
+const FINE: &str = "time:fine:";
+const DONE: &str = "Done";
+
+/// What to do with the data of a pressed picker button.
+pub enum PickerAction {
+    // show another page of the picker in the same message
+    Edit(Vec<Vec<InlineKeyboardButton>>),
+    Ignore,
+}
+
+/// Picker buttons that only page through the picker are handled here, anything
+/// else (e.g. a picked "09:15" or "2026-11-03") is a value for the wizard.
+pub fn handle_picker_data(data: &str, today: NaiveDate) -> Option<PickerAction> {
+    if data == NOOP {
+        return Some(PickerAction::Ignore);
+    }
+
+    if data == HOURS {
+        return Some(PickerAction::Edit(hour_grid()));
+    }
+
+    if let Some(hour) = data.strip_prefix(HOUR) {
+        return Some(match hour.parse::<u32>() {
+            Ok(hour) => PickerAction::Edit(minute_grid(hour, 15)),
+            Err(_) => PickerAction::Ignore,
+        });
+    }
+
+    if let Some(hour) = data.strip_prefix(FINE) {
+        return Some(match hour.parse::<u32>() {
+            Ok(hour) => PickerAction::Edit(minute_grid(hour, 5)),
+            Err(_) => PickerAction::Ignore,
+        });
+    }
+
+    if let Some(month) = data.strip_prefix(CALENDAR) {
+        return Some(
+            match NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d") {
+                Ok(first) => PickerAction::Edit(calendar(first, today)),
+                Err(_) => PickerAction::Ignore,
+            },
+        );
+    }
+
+    None
+}
+
+fn button(text: String, callback_data: String) -> InlineKeyboardButton {
+    InlineKeyboardButton {
+        text,
+        callback_data,
+    }
+}
+
+fn noop(text: &str) -> InlineKeyboardButton {
+    button(text.to_owned(), NOOP.to_owned())
+}
+
+fn back_row() -> Vec<InlineKeyboardButton> {
+    vec![button(BACK.to_owned(), BACK.to_owned())]
+}
+
+/// First page of the time picker, one button per hour.
+pub fn hour_grid() -> Vec<Vec<InlineKeyboardButton>> {
+    let mut rows = (0..24)
+        .collect::<Vec<u32>>()
+        .chunks(6)
+        .map(|hours| {
+            hours
+                .iter()
+                .map(|hour| button(format!("{:02}", hour), format!("{}{:02}", HOUR, hour)))
+                .collect()
+        })
+        .collect::<Vec<Vec<InlineKeyboardButton>>>();
+
+    rows.push(back_row());
+    rows
+}
+
+/// The time picker for several times. Times already picked are listed above
+/// the hours, pressing one removes it.
+pub fn time_picker(picked: &[String]) -> Vec<Vec<InlineKeyboardButton>> {
+    let mut rows = hour_grid();
+    if picked.is_empty() {
+        return rows;
+    }
+
+    let back = rows.pop().unwrap();
+    rows.extend(picked.chunks(4).map(|times| {
+        times
+            .iter()
+            .map(|time| button(format!("✓ {}", time), time.to_owned()))
+            .collect()
+    }));
+    rows.push(vec![button(DONE.to_owned(), DONE.to_owned())]);
+    rows.push(back);
+
+    rows
+}
+
+/// Second page of the time picker, the minutes of `hour` in steps of `step`.
+pub fn minute_grid(hour: u32, step: u32) -> Vec<Vec<InlineKeyboardButton>> {
+    let mut rows = (0..60)
+        .step_by(step as usize)
+        .collect::<Vec<u32>>()
+        .chunks(4)
+        .map(|minutes| {
+            minutes
+                .iter()
+                .map(|minute| {
+                    let time = format!("{:02}:{:02}", hour, minute);
+                    button(time.to_owned(), time)
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<InlineKeyboardButton>>>();
+
+    let mut navigation = vec![button("« Hours".to_owned(), HOURS.to_owned())];
+    if step != 5 {
+        navigation.push(button(
+            "5 minute steps".to_owned(),
+            format!("{}{:02}", FINE, hour),
+        ));
+    }
+    rows.push(navigation);
+    rows.push(back_row());
+
+    rows
+}
+
+/// A month of buttons starting at `first`, days before `today` can't be picked.
+pub fn calendar(first: NaiveDate, today: NaiveDate) -> Vec<Vec<InlineKeyboardButton>> {
+    let first = first.with_day(1).unwrap();
+    let previous = first.checked_sub_months(Months::new(1)).unwrap();
+    let next = first.checked_add_months(Months::new(1)).unwrap();
+
+    let mut rows = vec![
+        vec![
+            button(
+                "«".to_owned(),
+                format!("{}{}", CALENDAR, previous.format("%Y-%m")),
+            ),
+            noop(&first.format("%B %Y").to_string()),
+            button(
+                "»".to_owned(),
+                format!("{}{}", CALENDAR, next.format("%Y-%m")),
+            ),
+        ],
+        ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+            .iter()
+            .map(|day| noop(day))
+            .collect(),
+    ];
+
+    let mut week = (0..first.weekday().num_days_from_monday())
+        .map(|_| noop(" "))
+        .collect::<Vec<InlineKeyboardButton>>();
+
+    let mut day = first;
+    while day < next {
+        week.push(match day < today {
+            true => noop("·"),
+            false => button(day.day().to_string(), day.format("%Y-%m-%d").to_string()),
+        });
+
+        if week.len() == 7 {
+            rows.push(week);
+            week = Vec::new();
+        }
+        day = day.succ_opt().unwrap();
+    }
+
+    if !week.is_empty() {
+        while week.len() < 7 {
+            week.push(noop(" "));
+        }
+        rows.push(week);
+    }
+
+    rows.push(back_row());
+    rows
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_calendar() {
+        let first = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+        let rows = calendar(first, today);
+
+        // header, weekdays, five weeks as february 2026 starts on a sunday, back
+        assert_eq!(rows.len(), 2 + 5 + 1);
+        assert_eq!(rows[0][0].callback_data, "cal:2026-01");
+        assert_eq!(rows[0][2].callback_data, "cal:2026-03");
+        assert_eq!(rows[2][6].callback_data, NOOP);
+        assert_eq!(rows[4][1].callback_data, "2026-02-10");
+        assert_eq!(rows[6][5].callback_data, "2026-02-28");
+    }
+
+    #[test]
+    pub fn test_time_picker_pages() {
+        let today = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+
+        match handle_picker_data("time:hour:09", today) {
+            Some(PickerAction::Edit(rows)) => {
+                assert_eq!(rows[0][1].callback_data, "09:15");
+                assert_eq!(rows.len(), 1 + 2);
+            }
+            _ => panic!("expected the minutes of 09"),
+        }
+
+        assert!(handle_picker_data("09:15", today).is_none());
+
+        // hours, the picked times, done and back
+        let rows = time_picker(&["08:00".to_owned(), "12:00".to_owned()]);
+        assert_eq!(rows.len(), 4 + 1 + 1 + 1);
+        assert_eq!(rows[4][1].callback_data, "12:00");
+        assert!(handle_picker_data(DONE, today).is_none());
+        assert!(matches!(
+            handle_picker_data(NOOP, today),
+            Some(PickerAction::Ignore)
+        ));
+    }
+}